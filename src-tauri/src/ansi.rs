@@ -0,0 +1,262 @@
+// ANSI escape sequences in app output.
+//
+// SGR sequences (ESC [ ... m) become styled spans; every other escape
+// sequence (cursor movement, erase, window titles, ...) is dropped so it
+// doesn't show up as garbage in the log view or the log files.
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    /// One of the 16 terminal colors, 8-15 being the bright variants.
+    Palette(u8),
+    Rgb(u8, u8, u8),
+}
+
+const PALETTE_NAMES: [&str; 16] = [
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+    "brightBlack", "brightRed", "brightGreen", "brightYellow",
+    "brightBlue", "brightMagenta", "brightCyan", "brightWhite",
+];
+
+impl Color {
+    /// A color of the xterm 256-color table.
+    fn indexed(n: u8) -> Self {
+        match n {
+            0..=15 => Color::Palette(n),
+            16..=231 => {
+                let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+                let n = n - 16;
+                Color::Rgb(level(n / 36), level(n / 6 % 6), level(n % 6))
+            }
+            _ => {
+                let gray = 8 + (n - 232) * 10;
+                Color::Rgb(gray, gray, gray)
+            }
+        }
+    }
+}
+
+/// Palette colors by name ("red", "brightBlue"), others as "#rrggbb".
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Color::Palette(n) => f.write_str(PALETTE_NAMES[*n as usize % 16]),
+            Color::Rgb(r, g, b) => write!(f, "#{:02x}{:02x}{:02x}", r, g, b),
+        }
+    }
+}
+
+impl serde::Serialize for Color {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+pub struct Style {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fg: Option<Color>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bg: Option<Color>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub bold: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub dim: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub italic: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub underline: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct Span {
+    pub text: String,
+    #[serde(flatten)]
+    pub style: Style,
+}
+
+/// A line with its escape sequences removed.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct StyledText {
+    pub text: String,
+    /// Covers all of `text`; empty when the line has no styling at all.
+    pub spans: Vec<Span>,
+}
+
+impl StyledText {
+    pub fn plain(text: &str) -> Self {
+        Self { text: text.to_string(), spans: Vec::new() }
+    }
+}
+
+/// Parser for one output stream. The current style carries over from
+/// line to line, as it does in a terminal.
+#[derive(Debug, Clone, Default)]
+pub struct AnsiParser {
+    style: Style,
+}
+
+impl AnsiParser {
+    pub fn parse(&mut self, input: &str) -> StyledText {
+        let mut out = StyledText::default();
+        let mut chars = input.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c != '\x1b' {
+                out.text.push(c);
+                match out.spans.last_mut() {
+                    Some(span) if span.style == self.style => span.text.push(c),
+                    _ => out.spans.push(Span { text: c.to_string(), style: self.style }),
+                }
+                continue;
+            }
+            match chars.next() {
+                // CSI: parameters up to a final byte in '@'..='~'
+                Some('[') => {
+                    let mut params = String::new();
+                    for c in chars.by_ref() {
+                        if ('@'..='~').contains(&c) {
+                            if c == 'm' {
+                                self.apply_sgr(&params);
+                            }
+                            break;
+                        }
+                        params.push(c);
+                    }
+                }
+                // OSC (titles, hyperlinks): up to BEL or ESC \
+                Some(']') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' {
+                            break;
+                        }
+                        if c == '\x1b' {
+                            chars.next_if_eq(&'\\');
+                            break;
+                        }
+                    }
+                }
+                // Character set selection takes one more character
+                Some('(' | ')' | '*' | '+') => {
+                    chars.next();
+                }
+                _ => {}
+            }
+        }
+        if out.spans.iter().all(|span| span.style == Style::default()) {
+            out.spans.clear();
+        }
+        out
+    }
+
+    fn apply_sgr(&mut self, params: &str) {
+        // Sub-parameters ("38:5:n") are read like plain ones; an empty
+        // parameter counts as 0, so a bare "ESC [ m" resets
+        let mut codes = params.split([';', ':']).map(|p| p.parse::<u32>().unwrap_or(0));
+        let style = &mut self.style;
+        while let Some(c) = codes.next() {
+            match c {
+                0 => *style = Style::default(),
+                1 => style.bold = true,
+                2 => style.dim = true,
+                3 => style.italic = true,
+                4 => style.underline = true,
+                22 => {
+                    style.bold = false;
+                    style.dim = false;
+                }
+                23 => style.italic = false,
+                24 => style.underline = false,
+                30..=37 => style.fg = Some(Color::Palette((c - 30) as u8)),
+                39 => style.fg = None,
+                40..=47 => style.bg = Some(Color::Palette((c - 40) as u8)),
+                49 => style.bg = None,
+                90..=97 => style.fg = Some(Color::Palette((c - 90 + 8) as u8)),
+                100..=107 => style.bg = Some(Color::Palette((c - 100 + 8) as u8)),
+                38 | 48 => {
+                    let color = extended_color(&mut codes);
+                    if c == 38 {
+                        style.fg = color;
+                    } else {
+                        style.bg = color;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// The color after a 38/48 code: "5;n" or "2;r;g;b".
+fn extended_color(codes: &mut impl Iterator<Item = u32>) -> Option<Color> {
+    let byte = |v: Option<u32>| v.map(|v| v.min(255) as u8);
+    match codes.next()? {
+        5 => byte(codes.next()).map(Color::indexed),
+        2 => {
+            let (r, g, b) = (byte(codes.next())?, byte(codes.next())?, byte(codes.next())?);
+            Some(Color::Rgb(r, g, b))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn styled(text: &str, style: Style) -> Span {
+        Span { text: text.to_string(), style }
+    }
+
+    #[test]
+    fn plain_text_has_no_spans() {
+        let out = AnsiParser::default().parse("hello world");
+        assert_eq!(out.text, "hello world");
+        assert!(out.spans.is_empty());
+    }
+
+    #[test]
+    fn colors_and_attributes() {
+        let out = AnsiParser::default().parse("\x1b[1;32m   Compiling\x1b[0m app v1.0\x1b[4m!\x1b[24m");
+        assert_eq!(out.text, "   Compiling app v1.0!");
+        let green = Style { fg: Some(Color::Palette(2)), bold: true, ..Style::default() };
+        let underline = Style { underline: true, ..Style::default() };
+        assert_eq!(out.spans, [
+            styled("   Compiling", green),
+            styled(" app v1.0", Style::default()),
+            styled("!", underline),
+        ]);
+    }
+
+    #[test]
+    fn extended_colors() {
+        let out = AnsiParser::default().parse("\x1b[38;5;196;48;2;0;0;255mx\x1b[38:5:9my");
+        assert_eq!(out.spans[0].style.fg, Some(Color::Rgb(255, 0, 0)));
+        assert_eq!(out.spans[0].style.bg, Some(Color::Rgb(0, 0, 255)));
+        assert_eq!(out.spans[1].style.fg, Some(Color::Palette(9)));
+        assert_eq!(Color::indexed(232).to_string(), "#080808");
+        assert_eq!(Color::Palette(12).to_string(), "brightBlue");
+    }
+
+    #[test]
+    fn style_carries_across_lines() {
+        let mut parser = AnsiParser::default();
+        parser.parse("\x1b[31mfirst");
+        let out = parser.parse("second\x1b[m");
+        assert_eq!(out.spans, [styled("second", Style { fg: Some(Color::Palette(1)), ..Style::default() })]);
+        assert!(parser.parse("third").spans.is_empty());
+    }
+
+    #[test]
+    fn other_sequences_are_dropped() {
+        let out = AnsiParser::default().parse("\x1b]0;title\x07\x1b[2K\x1b(Bok \x1b]8;;http://x\x1b\\link\x1b]8;;\x1b\\");
+        assert_eq!(out.text, "ok link");
+        assert!(out.spans.is_empty());
+    }
+
+    #[test]
+    fn serializes_compactly() {
+        let span = styled("x", Style { fg: Some(Color::Palette(1)), bold: true, ..Style::default() });
+        assert_eq!(serde_json::to_string(&span).unwrap(), r#"{"text":"x","fg":"red","bold":true}"#);
+    }
+}
@@ -0,0 +1,262 @@
+// Turns the raw bytes an app writes to its pipes into lines of text.
+//
+// Output is split on '\n' before decoding, so multi-byte characters never
+// straddle a chunk boundary. A '\r' that isn't part of "\r\n" rewinds the
+// line the way a terminal would, which keeps progress bars to one line.
+
+use std::time::{Duration, Instant};
+
+/// How long an unfinished line may sit before it is shown anyway.
+pub const PARTIAL_FLUSH: Duration = Duration::from_millis(150);
+/// A line this long is cut even without a newline.
+const MAX_LINE_BYTES: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputEncoding {
+    /// UTF-8, falling back to the console code page for lines that
+    /// aren't valid UTF-8.
+    Auto,
+    Utf8,
+    /// A Windows code page such as 1258 or 936.
+    CodePage(u32),
+}
+
+impl OutputEncoding {
+    /// Accepts "auto", "utf-8", or a code page as "1258", "cp1258" or
+    /// "windows-1258".
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let s = s.trim().to_ascii_lowercase();
+        match s.as_str() {
+            "" | "auto" => return Ok(OutputEncoding::Auto),
+            "utf-8" | "utf8" | "65001" | "cp65001" => return Ok(OutputEncoding::Utf8),
+            _ => {}
+        }
+        let number = s
+            .strip_prefix("windows-")
+            .or_else(|| s.strip_prefix("cp"))
+            .or_else(|| s.strip_prefix("ibm"))
+            .unwrap_or(&s);
+        match number.parse::<u32>() {
+            Ok(code_page) if code_page_supported(code_page) => Ok(OutputEncoding::CodePage(code_page)),
+            Ok(code_page) => Err(format!("Code page {} is not available", code_page)),
+            Err(_) => Err(format!("Unknown encoding {:?}", s)),
+        }
+    }
+
+    /// Whether apps should be asked to write UTF-8 (PYTHONUTF8 etc.).
+    pub fn wants_utf8(&self) -> bool {
+        !matches!(self, OutputEncoding::CodePage(_))
+    }
+
+    pub fn decode(&self, bytes: &[u8]) -> String {
+        match self {
+            OutputEncoding::Utf8 => String::from_utf8_lossy(bytes).into_owned(),
+            OutputEncoding::Auto => match std::str::from_utf8(bytes) {
+                Ok(text) => text.to_string(),
+                Err(_) => console_code_page()
+                    .and_then(|code_page| decode_code_page(code_page, bytes))
+                    .unwrap_or_else(|| String::from_utf8_lossy(bytes).into_owned()),
+            },
+            OutputEncoding::CodePage(code_page) => decode_code_page(*code_page, bytes)
+                .unwrap_or_else(|| String::from_utf8_lossy(bytes).into_owned()),
+        }
+    }
+}
+
+#[cfg(windows)]
+fn code_page_supported(code_page: u32) -> bool {
+    unsafe { winapi::um::winnls::IsValidCodePage(code_page) != 0 }
+}
+
+#[cfg(not(windows))]
+fn code_page_supported(_code_page: u32) -> bool {
+    false
+}
+
+/// Code page console programs write in when not told otherwise.
+#[cfg(windows)]
+fn console_code_page() -> Option<u32> {
+    Some(unsafe { winapi::um::winnls::GetOEMCP() })
+}
+
+#[cfg(not(windows))]
+fn console_code_page() -> Option<u32> {
+    None
+}
+
+#[cfg(windows)]
+fn decode_code_page(code_page: u32, bytes: &[u8]) -> Option<String> {
+    use winapi::um::stringapiset::MultiByteToWideChar;
+
+    if bytes.is_empty() {
+        return Some(String::new());
+    }
+    let len = i32::try_from(bytes.len()).ok()?;
+    unsafe {
+        let needed = MultiByteToWideChar(code_page, 0, bytes.as_ptr() as *const i8, len, std::ptr::null_mut(), 0);
+        if needed <= 0 {
+            return None;
+        }
+        let mut wide = vec![0u16; needed as usize];
+        let written = MultiByteToWideChar(code_page, 0, bytes.as_ptr() as *const i8, len, wide.as_mut_ptr(), needed);
+        if written <= 0 {
+            return None;
+        }
+        wide.truncate(written as usize);
+        Some(String::from_utf16_lossy(&wide))
+    }
+}
+
+#[cfg(not(windows))]
+fn decode_code_page(_code_page: u32, _bytes: &[u8]) -> Option<String> {
+    None
+}
+
+/// Splits one stream into lines, holding back the unfinished tail.
+#[derive(Default)]
+pub struct LineAssembler {
+    pending: Vec<u8>,
+    /// When the tail last changed without being shown.
+    unshown_since: Option<Instant>,
+}
+
+impl LineAssembler {
+    /// Add a chunk, returning the lines it completed.
+    pub fn push(&mut self, bytes: &[u8], now: Instant) -> Vec<Vec<u8>> {
+        let mut lines = Vec::new();
+        let mut rest = bytes;
+        while let Some(pos) = rest.iter().position(|&b| b == b'\n') {
+            self.pending.extend_from_slice(&rest[..pos]);
+            lines.push(visible(&std::mem::take(&mut self.pending)).to_vec());
+            rest = &rest[pos + 1..];
+        }
+        self.pending.extend_from_slice(rest);
+
+        // Text before the last rewind can't be seen any more; a trailing
+        // '\r' is kept in case the '\n' of a "\r\n" is still on its way
+        let body = self.pending.len().saturating_sub(1);
+        if let Some(pos) = self.pending[..body].iter().rposition(|&b| b == b'\r') {
+            self.pending.drain(..=pos);
+        }
+        if self.pending.len() >= MAX_LINE_BYTES {
+            lines.push(std::mem::take(&mut self.pending));
+        }
+
+        if self.pending.is_empty() {
+            self.unshown_since = None;
+        } else if !rest.is_empty() || !lines.is_empty() {
+            self.unshown_since.get_or_insert(now);
+        }
+        lines
+    }
+
+    /// When `take_partial` will next have something to show.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.unshown_since.map(|since| since + PARTIAL_FLUSH)
+    }
+
+    /// The unfinished line, once it has waited `PARTIAL_FLUSH`.
+    pub fn take_partial(&mut self, now: Instant) -> Option<Vec<u8>> {
+        if self.deadline()? > now {
+            return None;
+        }
+        self.unshown_since = None;
+        let text = visible(&self.pending);
+        (!text.is_empty()).then(|| text.to_vec())
+    }
+
+    /// Whatever is left once the stream has closed.
+    pub fn finish(&mut self) -> Option<Vec<u8>> {
+        self.unshown_since = None;
+        let rest = std::mem::take(&mut self.pending);
+        (!rest.is_empty()).then(|| visible(&rest).to_vec())
+    }
+}
+
+/// What a terminal would show for `line`: the text after its last '\r'.
+fn visible(line: &[u8]) -> &[u8] {
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    match line.iter().rposition(|&b| b == b'\r') {
+        Some(pos) => &line[pos + 1..],
+        None => line,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(lines: Vec<Vec<u8>>) -> Vec<String> {
+        lines.into_iter().map(|l| String::from_utf8(l).unwrap()).collect()
+    }
+
+    #[test]
+    fn splits_lines_across_chunks() {
+        let mut a = LineAssembler::default();
+        let now = Instant::now();
+        assert_eq!(texts(a.push(b"one\r\ntw", now)), ["one"]);
+        assert_eq!(texts(a.push(b"o\r", now)), Vec::<String>::new());
+        assert_eq!(texts(a.push(b"\nthree\n", now)), ["two", "three"]);
+        assert_eq!(a.finish(), None);
+    }
+
+    #[test]
+    fn carriage_returns_overwrite() {
+        let mut a = LineAssembler::default();
+        let now = Instant::now();
+        assert!(a.push(b"10%\r20%\r", now).is_empty());
+        assert_eq!(a.pending, b"20%\r");
+        assert_eq!(texts(a.push(b"100%\ndone\n", now)), ["100%", "done"]);
+    }
+
+    #[test]
+    fn partial_lines_wait_for_the_flush_delay() {
+        let mut a = LineAssembler::default();
+        let start = Instant::now();
+        a.push(b"Downloading... 5%", start);
+        assert_eq!(a.take_partial(start), None);
+        assert_eq!(a.deadline(), Some(start + PARTIAL_FLUSH));
+        let later = start + PARTIAL_FLUSH;
+        assert_eq!(a.take_partial(later).unwrap(), b"Downloading... 5%");
+        // Nothing new to show until more bytes arrive
+        assert_eq!(a.deadline(), None);
+        a.push(b"\rDownloading... 9%", later);
+        assert_eq!(a.take_partial(later + PARTIAL_FLUSH).unwrap(), b"Downloading... 9%");
+        assert_eq!(texts(a.push(b"\n", later)), ["Downloading... 9%"]);
+        assert_eq!(a.deadline(), None);
+    }
+
+    #[test]
+    fn unterminated_tail_is_kept_until_finish() {
+        let mut a = LineAssembler::default();
+        a.push(b"prompt> ", Instant::now());
+        assert_eq!(a.finish().unwrap(), b"prompt> ");
+        assert_eq!(a.finish(), None);
+    }
+
+    #[test]
+    fn overlong_lines_are_cut() {
+        let mut a = LineAssembler::default();
+        let lines = a.push(&vec![b'x'; MAX_LINE_BYTES + 10], Instant::now());
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].len(), MAX_LINE_BYTES + 10);
+        assert_eq!(a.finish(), None);
+    }
+
+    #[test]
+    fn encodings() {
+        assert_eq!(OutputEncoding::parse(""), Ok(OutputEncoding::Auto));
+        assert_eq!(OutputEncoding::parse("UTF8"), Ok(OutputEncoding::Utf8));
+        assert!(OutputEncoding::parse("latin-whatever").is_err());
+        assert_eq!(OutputEncoding::Auto.decode("Xin chào".as_bytes()), "Xin chào");
+        // Invalid UTF-8 still produces a line instead of being dropped
+        assert!(!OutputEncoding::Utf8.decode(b"caf\xe9").is_empty());
+        if cfg!(windows) {
+            assert_eq!(OutputEncoding::parse("cp1252"), Ok(OutputEncoding::CodePage(1252)));
+            assert_eq!(OutputEncoding::CodePage(1252).decode(b"caf\xe9"), "café");
+        } else {
+            assert!(OutputEncoding::parse("1252").is_err());
+            assert_eq!(OutputEncoding::Auto.decode(b"caf\xe9"), "caf\u{fffd}");
+        }
+    }
+}
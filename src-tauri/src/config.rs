@@ -0,0 +1,915 @@
+// The config.json model.
+//
+// The frontend edits apps, groups and settings, but the backend owns the
+// schema: what it saves is checked by `validate` before it is written, so
+// the file only holds apps that can be launched. Files written by older
+// versions are upgraded by `migrate`, and keys this version doesn't know
+// are kept so newer copies of AppCtrl don't lose data. Writes go through
+// a temporary file and keep backups, which a damaged file is recovered
+// from. Kept free of Tauri types so the rules can be tested on their own.
+
+use crate::{deps, groups, health, launch, logs, triggers};
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// The schema this version writes. A change to what stored fields mean
+/// gets a new version and an entry in MIGRATIONS.
+pub const SCHEMA_VERSION: u64 = 1;
+
+/// `MIGRATIONS[n]` upgrades a config from version n to n + 1. Files from
+/// before versioning are version 0.
+const MIGRATIONS: [fn(&mut Map<String, Value>); SCHEMA_VERSION as usize] = [unversioned_to_1];
+
+/// Backups kept next to config.json; older ones are deleted on save.
+pub const BACKUPS_KEPT: usize = 10;
+
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct ConfigData {
+    /// Files are migrated before they are parsed, so a missing version
+    /// only comes from the frontend, which saves the current one.
+    #[serde(rename = "schemaVersion", default = "current_schema")]
+    pub schema_version: u64,
+    #[serde(default)]
+    pub apps: Vec<AppConfig>,
+    #[serde(default)]
+    pub groups: Vec<groups::GroupConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub settings: Option<AppSettings>,
+    /// Process names hidden by the task killer, keyed by OS ("windows",
+    /// "linux", ...).
+    #[serde(rename = "systemProcesses", skip_serializing_if = "Option::is_none")]
+    pub system_processes: Option<HashMap<String, Vec<String>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logs: Option<logs::LogSettings>,
+    /// Keys this version doesn't know, kept as they are.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+fn current_schema() -> u64 {
+    SCHEMA_VERSION
+}
+
+impl Default for ConfigData {
+    fn default() -> Self {
+        ConfigData {
+            schema_version: SCHEMA_VERSION,
+            apps: Vec::new(),
+            groups: Vec::new(),
+            settings: None,
+            system_processes: None,
+            logs: None,
+            extra: Map::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct AppConfig {
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "executablePath")]
+    pub executable_path: String,
+    #[serde(rename = "appType")]
+    pub app_type: String,
+    #[serde(rename = "workingDirectory")]
+    pub working_directory: Option<String>,
+    #[serde(rename = "arguments")]
+    pub arguments: Option<String>,
+    #[serde(rename = "environmentVars")]
+    pub environment_vars: Option<String>,
+    #[serde(rename = "shell")]
+    pub shell: Option<String>,
+    #[serde(rename = "stopSignal")]
+    pub stop_signal: Option<String>,
+    #[serde(rename = "stopTimeout")]
+    pub stop_timeout: Option<u64>,
+    #[serde(rename = "restartPolicy")]
+    pub restart_policy: Option<String>,
+    #[serde(rename = "maxRestarts")]
+    pub max_restarts: Option<u32>,
+    #[serde(rename = "encoding")]
+    pub encoding: Option<String>,
+    #[serde(rename = "forceColor")]
+    pub force_color: Option<bool>,
+    #[serde(rename = "terminal")]
+    pub terminal: Option<bool>,
+    #[serde(rename = "keepStdin")]
+    pub keep_stdin: Option<bool>,
+    #[serde(rename = "triggers")]
+    pub triggers: Option<Vec<triggers::TriggerRule>>,
+    #[serde(rename = "health")]
+    pub health: Option<health::HealthOptions>,
+    #[serde(rename = "dependsOn")]
+    pub depends_on: Option<Vec<String>>,
+    #[serde(rename = "icon")]
+    pub icon: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppSettings {
+    #[serde(default = "default_minimize_to_tray")]
+    pub minimize_to_tray: bool,
+    #[serde(default)]
+    pub theme: Theme,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+fn default_minimize_to_tray() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+}
+
+/// One problem with one field, e.g. the working directory of an app.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldError {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub app_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_id: Option<String>,
+    /// The JSON key, as the frontend names it.
+    pub field: &'static str,
+    pub message: String,
+}
+
+impl FieldError {
+    fn app(app: &AppConfig, field: &'static str, message: impl Into<String>) -> Self {
+        Self { app_id: Some(app.id.clone()), group_id: None, field, message: message.into() }
+    }
+
+    fn group(group: &groups::GroupConfig, field: &'static str, message: impl Into<String>) -> Self {
+        Self { app_id: None, group_id: Some(group.id.clone()), field, message: message.into() }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    Invalid(Vec<FieldError>),
+    /// Reading, parsing or writing config.json failed.
+    Io(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Invalid(errors) if errors.len() == 1 => write!(f, "{}", errors[0].message),
+            ConfigError::Invalid(errors) => write!(f, "The config has {} problems", errors.len()),
+            ConfigError::Io(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+// Sent to the frontend as { kind, message, errors }
+impl serde::Serialize for ConfigError {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let (kind, errors) = match self {
+            ConfigError::Invalid(errors) => ("invalid", errors.as_slice()),
+            ConfigError::Io(_) => ("io", &[][..]),
+        };
+        let mut s = serializer.serialize_struct("ConfigError", 3)?;
+        s.serialize_field("kind", kind)?;
+        s.serialize_field("message", &self.to_string())?;
+        s.serialize_field("errors", errors)?;
+        s.end()
+    }
+}
+
+/// What changed between two configs, by id.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigDiff {
+    pub added_apps: Vec<String>,
+    pub removed_apps: Vec<String>,
+    pub changed_apps: Vec<String>,
+    pub added_groups: Vec<String>,
+    pub removed_groups: Vec<String>,
+    pub changed_groups: Vec<String>,
+    /// Anything outside apps and groups: settings, log rotation, ...
+    pub settings_changed: bool,
+}
+
+impl ConfigDiff {
+    pub fn is_empty(&self) -> bool {
+        *self == ConfigDiff::default()
+    }
+}
+
+/// Ids added, removed and changed between `old` and `new`, in the order
+/// they appear.
+fn diff_by_id<T: serde::Serialize>(old: &[T], new: &[T], id: fn(&T) -> &str) -> [Vec<String>; 3] {
+    let values = |items: &[T]| -> Vec<(String, Value)> {
+        items.iter().map(|item| (id(item).to_string(), serde_json::to_value(item).unwrap_or_default())).collect()
+    };
+    let (old, new) = (values(old), values(new));
+    let find = |items: &[(String, Value)], wanted: &str| items.iter().find(|(id, _)| id == wanted).map(|(_, value)| value.clone());
+    let ids = |items: &[(String, Value)], keep: &dyn Fn(&String, &Value) -> bool| {
+        items.iter().filter(|(id, value)| keep(id, value)).map(|(id, _)| id.clone()).collect()
+    };
+    [
+        ids(&new, &|id, _| find(&old, id).is_none()),
+        ids(&old, &|id, _| find(&new, id).is_none()),
+        ids(&new, &|id, value| find(&old, id).is_some_and(|old| old != *value)),
+    ]
+}
+
+impl ConfigData {
+    /// What `newer` changes compared to this config.
+    pub fn diff(&self, newer: &ConfigData) -> ConfigDiff {
+        let [added_apps, removed_apps, changed_apps] = diff_by_id(&self.apps, &newer.apps, |app| &app.id);
+        let [added_groups, removed_groups, changed_groups] = diff_by_id(&self.groups, &newer.groups, |group| &group.id);
+        let rest = |config: &ConfigData| {
+            serde_json::to_value(ConfigData { apps: Vec::new(), groups: Vec::new(), ..config.clone() }).unwrap_or_default()
+        };
+        ConfigDiff {
+            added_apps,
+            removed_apps,
+            changed_apps,
+            added_groups,
+            removed_groups,
+            changed_groups,
+            settings_changed: rest(self) != rest(newer),
+        }
+    }
+
+    pub fn dependency_graph(&self) -> deps::Graph {
+        self.apps.iter().map(|app| (app.id.clone(), app.depends_on.clone().unwrap_or_default())).collect()
+    }
+
+    pub fn app_names(&self) -> HashMap<String, String> {
+        self.apps.iter().map(|app| (app.id.clone(), app.name.clone())).collect()
+    }
+
+    /// Check the config before it is saved over `previous`. Paths are only
+    /// checked for apps whose launch settings changed, so a program that
+    /// has since been uninstalled doesn't block saving anything else.
+    pub fn validate(&self, previous: &ConfigData) -> Result<(), ConfigError> {
+        let mut errors = Vec::new();
+
+        let mut ids = HashSet::new();
+        for app in &self.apps {
+            if app.id.trim().is_empty() {
+                errors.push(FieldError::app(app, "id", format!("{} has no id", app.name)));
+            } else if !ids.insert(app.id.as_str()) {
+                errors.push(FieldError::app(app, "id", format!("Duplicate app id {:?}", app.id)));
+            }
+            if app.name.trim().is_empty() {
+                errors.push(FieldError::app(app, "name", "Name is required"));
+            }
+            let unchanged = previous.apps.iter().any(|old| {
+                old.id == app.id
+                    && old.app_type == app.app_type
+                    && old.executable_path == app.executable_path
+                    && old.working_directory == app.working_directory
+            });
+            if !unchanged {
+                check_paths(app, &mut errors);
+            }
+            check_fields(app, &mut errors);
+        }
+
+        if let Err(e) = deps::check(&self.dependency_graph()) {
+            let app_id = match &e {
+                deps::DependencyError::Unknown { app, .. } => app.clone(),
+                deps::DependencyError::Cycle(ids) => ids[0].clone(),
+            };
+            errors.push(FieldError {
+                app_id: Some(app_id),
+                group_id: None,
+                field: "dependsOn",
+                message: e.describe(&self.app_names()),
+            });
+        }
+
+        let mut group_ids = HashSet::new();
+        for group in &self.groups {
+            if !group_ids.insert(group.id.as_str()) {
+                errors.push(FieldError::group(group, "id", format!("Duplicate group id {:?}", group.id)));
+            }
+            if group.name.trim().is_empty() {
+                errors.push(FieldError::group(group, "name", "Group name is required"));
+            }
+            if let Some(missing) = group.apps.iter().find(|id| !ids.contains(id.as_str())) {
+                errors.push(FieldError::group(group, "apps", format!("{} contains an unknown app ({})", group.name, missing)));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Invalid(errors))
+        }
+    }
+}
+
+/// Bring a parsed config.json up to SCHEMA_VERSION and return the version
+/// it was written with. Files from a newer AppCtrl are left as they are.
+pub fn migrate(value: &mut Value) -> Result<u64, String> {
+    let config = value.as_object_mut().ok_or("config.json does not hold a JSON object")?;
+    let version = match config.get("schemaVersion") {
+        None => 0,
+        Some(version) => version.as_u64().ok_or_else(|| format!("Invalid schemaVersion {}", version))?,
+    };
+    for migration in MIGRATIONS.iter().skip(version as usize) {
+        migration(config);
+    }
+    if version < SCHEMA_VERSION {
+        config.insert("schemaVersion".to_string(), SCHEMA_VERSION.into());
+    }
+    Ok(version)
+}
+
+/// Files written before there was a schemaVersion:
+/// - `isRunning` was saved with each app, so apps AppCtrl doesn't poll
+///   (shell, bat) looked running after a restart.
+/// - Environment lines without `=` were skipped at launch. `validate`
+///   rejects them now, which would keep old configs from being saved.
+fn unversioned_to_1(config: &mut Map<String, Value>) {
+    let Some(Value::Array(apps)) = config.get_mut("apps") else { return };
+    for app in apps.iter_mut().filter_map(Value::as_object_mut) {
+        app.remove("isRunning");
+        if let Some(Value::String(env)) = app.get_mut("environmentVars") {
+            *env = env.lines().filter(|line| line.contains('=')).collect::<Vec<_>>().join("\n");
+        }
+    }
+}
+
+/// Parse config.json, upgrading it from older schemas. Also returns the
+/// version it was written with.
+pub fn parse(json: &[u8]) -> Result<(ConfigData, u64), String> {
+    let mut value: Value = serde_json::from_slice(json).map_err(|e| e.to_string())?;
+    let version = migrate(&mut value)?;
+    let config = serde_json::from_value(value).map_err(|e| e.to_string())?;
+    Ok((config, version))
+}
+
+/// The error for a file that failed to parse because a newer AppCtrl
+/// wrote it, e.g. with a theme this version doesn't know. Such a file is
+/// left alone instead of being recovered from a backup, which in a synced
+/// folder would revert the newer copy.
+fn from_newer_version(json: &[u8], error: &str) -> Option<ConfigError> {
+    let version = serde_json::from_slice::<Value>(json).ok()?.get("schemaVersion")?.as_u64()?;
+    (version > SCHEMA_VERSION).then(|| {
+        ConfigError::Io(format!(
+            "config.json is from a newer AppCtrl (schema {}, this one reads up to {}) and was left unchanged: {}",
+            version, SCHEMA_VERSION, error
+        ))
+    })
+}
+
+/// config.json and its backups, kept in `config-backups` next to it.
+pub struct ConfigFile {
+    path: PathBuf,
+}
+
+pub struct Loaded {
+    pub config: ConfigData,
+    /// The schema the file was written with.
+    pub version: u64,
+    /// Set when config.json could not be parsed.
+    pub recovery: Option<Recovery>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Recovery {
+    /// Why config.json was rejected.
+    pub error: String,
+    /// File name of the backup used instead; none if no backup was good.
+    pub backup: Option<String>,
+    /// Where the damaged file was moved.
+    pub corrupt_copy: Option<String>,
+}
+
+impl ConfigFile {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    fn backup_dir(&self) -> PathBuf {
+        self.path.with_file_name("config-backups")
+    }
+
+    /// The saved config, empty if there is no file yet. If config.json
+    /// can't be parsed, the newest backup that can is used instead, unless
+    /// a newer AppCtrl wrote it. Changes nothing on disk.
+    pub fn load(&self) -> Result<Loaded, ConfigError> {
+        let json = match fs::read(&self.path) {
+            Ok(json) => json,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                return Ok(Loaded { config: ConfigData::default(), version: SCHEMA_VERSION, recovery: None });
+            }
+            Err(e) => return Err(ConfigError::Io(format!("Could not read config.json: {}", e))),
+        };
+        let error = match parse(&json) {
+            Ok((config, version)) => return Ok(Loaded { config, version, recovery: None }),
+            Err(e) => e,
+        };
+        if let Some(e) = from_newer_version(&json, &error) {
+            return Err(e);
+        }
+        let backup = self.backups().into_iter().find_map(|path| {
+            let (config, version) = parse(&fs::read(&path).ok()?).ok()?;
+            Some((config, version, path.file_name()?.to_string_lossy().into_owned()))
+        });
+        let (config, version, backup) = match backup {
+            Some((config, version, name)) => (config, version, Some(name)),
+            None => (ConfigData::default(), SCHEMA_VERSION, None),
+        };
+        Ok(Loaded { config, version, recovery: Some(Recovery { error, backup, corrupt_copy: None }) })
+    }
+
+    /// Like `load`, but also fixes the file: a damaged config.json is
+    /// moved aside and the recovered config written in its place, so it is
+    /// only reported once, and an older schema is written upgraded.
+    pub fn recover(&self) -> Result<Loaded, ConfigError> {
+        let mut loaded = self.load()?;
+        if loaded.recovery.is_none() && loaded.version < SCHEMA_VERSION {
+            // The old file is kept among the backups
+            self.save(&loaded.config)?;
+        }
+        if let Some(recovery) = &mut loaded.recovery {
+            let name = format!("config.json.corrupt-{}", logs::new_run_id(SystemTime::now()));
+            let aside = self.path.with_file_name(name);
+            // If either step fails the next load recovers again
+            if fs::rename(&self.path, &aside).is_ok() {
+                recovery.corrupt_copy = Some(aside.to_string_lossy().into_owned());
+                if recovery.backup.is_some() {
+                    let _ = self.write(&loaded.config);
+                }
+            }
+        }
+        Ok(loaded)
+    }
+
+    /// Replace the file, backing up both the current one (which may have
+    /// been edited by hand) and the new one, so the newest backup is the
+    /// last good save.
+    pub fn save(&self, config: &ConfigData) -> Result<(), ConfigError> {
+        if let Ok(current) = fs::read(&self.path) {
+            if let Some(e) = parse(&current).err().and_then(|error| from_newer_version(&current, &error)) {
+                return Err(e);
+            }
+        }
+        // A failed backup shouldn't keep the user from saving
+        let _ = self.back_up(SystemTime::now());
+        self.write(config)?;
+        let _ = self.back_up(SystemTime::now());
+        Ok(())
+    }
+
+    fn write(&self, config: &ConfigData) -> Result<(), ConfigError> {
+        // Never downgrade a file written by a newer AppCtrl
+        let config = ConfigData { schema_version: config.schema_version.max(SCHEMA_VERSION), ..config.clone() };
+        let json = serde_json::to_string_pretty(&config).map_err(|e| ConfigError::Io(e.to_string()))?;
+        write_atomic(&self.path, json.as_bytes()).map_err(|e| ConfigError::Io(format!("Could not write config.json: {}", e)))
+    }
+
+    /// Copy config.json into the backups, unless it is damaged or the
+    /// same as the newest backup, and delete the ones beyond BACKUPS_KEPT.
+    fn back_up(&self, now: SystemTime) -> io::Result<()> {
+        let current = match fs::read(&self.path) {
+            Ok(current) => current,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        if parse(&current).is_err() {
+            return Ok(());
+        }
+        let newest = self.backups().first().and_then(|path| fs::read(path).ok());
+        if newest.as_deref() == Some(current.as_slice()) {
+            return Ok(());
+        }
+        let dir = self.backup_dir();
+        fs::create_dir_all(&dir)?;
+        // The backups before and after a save can fall in the same millisecond
+        let mut now = now;
+        let mut backup = dir.join(format!("config-{}.json", logs::new_run_id(now)));
+        while backup.exists() {
+            now += Duration::from_millis(1);
+            backup = dir.join(format!("config-{}.json", logs::new_run_id(now)));
+        }
+        fs::write(backup, &current)?;
+        for old in self.backups().iter().skip(BACKUPS_KEPT) {
+            fs::remove_file(old)?;
+        }
+        Ok(())
+    }
+
+    /// Backup files, newest first.
+    fn backups(&self) -> Vec<PathBuf> {
+        let Ok(entries) = fs::read_dir(self.backup_dir()) else { return Vec::new() };
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                name.starts_with("config-") && name.ends_with(".json")
+            })
+            .collect();
+        // Timestamped names sort by age
+        paths.sort_by(|a, b| b.cmp(a));
+        paths
+    }
+}
+
+/// Write to a temporary file next to `path` and rename it over `path`, so
+/// a crash or a full disk leaves either the old file or the new one.
+fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let tmp = path.with_extension("json.tmp");
+    let result = fs::File::create(&tmp).and_then(|mut file| {
+        file.write_all(contents)?;
+        file.sync_all()?;
+        drop(file);
+        fs::rename(&tmp, path)
+    });
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+/// The executable and working directory must exist.
+fn check_paths(app: &AppConfig, errors: &mut Vec<FieldError>) {
+    let working_dir = app.working_directory.as_deref().map(str::trim).filter(|dir| !dir.is_empty());
+    if let Some(dir) = working_dir {
+        if !Path::new(dir).is_dir() {
+            errors.push(FieldError::app(app, "workingDirectory", format!("{}: folder not found: {}", app.name, dir)));
+        }
+    }
+    let path = app.executable_path.trim();
+    if path.is_empty() {
+        let what = if app.app_type == "shell" { "Command" } else { "Path" };
+        errors.push(FieldError::app(app, "executablePath", format!("{}: {} is required", app.name, what.to_lowercase())));
+    } else if app.app_type != "shell" && find_program(path, working_dir.map(Path::new)).is_none() {
+        // Shell apps run a command line, there is no single file to check
+        errors.push(FieldError::app(app, "executablePath", format!("{}: file not found: {}", app.name, path)));
+    }
+}
+
+/// The fields that are parsed again on every launch.
+fn check_fields(app: &AppConfig, errors: &mut Vec<FieldError>) {
+    let mut check = |field, result: Result<(), String>| {
+        if let Err(e) = result {
+            errors.push(FieldError::app(app, field, format!("{}: {}", app.name, e)));
+        }
+    };
+    check("arguments", launch::split_args(app.arguments.as_deref().unwrap_or_default()).map(drop));
+    check("environmentVars", launch::parse_env(app.environment_vars.as_deref().unwrap_or_default()).map(drop));
+    check("triggers", triggers::Triggers::compile(app.triggers.as_deref().unwrap_or_default()).map(drop));
+    check("health", health::HealthChecks::compile(&app.health.clone().unwrap_or_default()).map(drop));
+}
+
+/// Where `path` is found: as given (relative to the working directory),
+/// or, for a bare name like `node` or `start.bat`, in the working
+/// directory or on PATH.
+fn find_program(path: &str, working_dir: Option<&Path>) -> Option<PathBuf> {
+    let candidate = Path::new(path);
+    if candidate.components().count() > 1 || candidate.is_absolute() {
+        let full = match working_dir {
+            Some(dir) if candidate.is_relative() => dir.join(candidate),
+            _ => candidate.to_path_buf(),
+        };
+        return full.is_file().then_some(full);
+    }
+    let names: Vec<String> = if cfg!(windows) && candidate.extension().is_none() {
+        ["exe", "cmd", "bat", "com"].iter().map(|ext| format!("{}.{}", path, ext)).collect()
+    } else {
+        vec![path.to_string()]
+    };
+    let path_dirs = std::env::var_os("PATH").map(|paths| std::env::split_paths(&paths).collect::<Vec<_>>());
+    working_dir
+        .map(Path::to_path_buf)
+        .into_iter()
+        .chain(path_dirs.unwrap_or_default())
+        .flat_map(|dir| names.iter().map(move |name| dir.join(name)))
+        .find(|full| full.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app(id: &str, json: &str) -> AppConfig {
+        let mut value: serde_json::Value = serde_json::from_str(json).unwrap();
+        value["id"] = id.into();
+        if value.get("name").is_none() {
+            value["name"] = id.into();
+        }
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn errors(config: &ConfigData) -> Vec<(Option<String>, &'static str)> {
+        match config.validate(&ConfigData::default()) {
+            Ok(()) => Vec::new(),
+            Err(ConfigError::Invalid(errors)) => errors.into_iter().map(|e| (e.app_id.or(e.group_id), e.field)).collect(),
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    fn exe() -> String {
+        std::env::current_exe().unwrap().to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn valid_config_passes() {
+        let dir = std::env::temp_dir().to_string_lossy().into_owned();
+        let config = ConfigData {
+            apps: vec![
+                app("a", &serde_json::json!({"executablePath": exe(), "appType": "exe", "workingDirectory": dir}).to_string()),
+                app("b", r#"{"executablePath":"npm run dev","appType":"shell","environmentVars":"PORT=3000\n","dependsOn":["a"]}"#),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(config.validate(&ConfigData::default()), Ok(()));
+    }
+
+    #[test]
+    fn field_errors_name_the_app_and_field() {
+        let config = ConfigData {
+            apps: vec![
+                app("a", r#"{"executablePath":"/no/such/app","appType":"exe","workingDirectory":"/no/such/dir"}"#),
+                app("b", r#"{"executablePath":"x","appType":"shell","environmentVars":"PORT 3000","arguments":"\"open"}"#),
+                app("b", r#"{"executablePath":"y","appType":"shell","dependsOn":["gone"]}"#),
+            ],
+            groups: vec![groups::GroupConfig { id: "g".into(), name: "Stack".into(), apps: vec!["zz".into()], extra: Map::new() }],
+            ..Default::default()
+        };
+        let some = |id: &str| Some(id.to_string());
+        assert_eq!(
+            errors(&config),
+            [
+                (some("a"), "workingDirectory"),
+                (some("a"), "executablePath"),
+                (some("b"), "arguments"),
+                (some("b"), "environmentVars"),
+                (some("b"), "id"),
+                (some("b"), "dependsOn"),
+                (some("g"), "apps"),
+            ]
+        );
+    }
+
+    #[test]
+    fn unchanged_paths_are_not_checked_again() {
+        let config = ConfigData {
+            apps: vec![app("a", r#"{"executablePath":"/uninstalled/app","appType":"exe"}"#)],
+            ..Default::default()
+        };
+        assert!(config.validate(&config.clone()).is_ok());
+        assert!(config.validate(&ConfigData::default()).is_err());
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("appctrl-config-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn named(name: &str) -> ConfigData {
+        ConfigData { apps: vec![app("a", &format!(r#"{{"name":"{}","executablePath":"x","appType":"shell"}}"#, name))], ..Default::default() }
+    }
+
+    fn app_name(file: &ConfigFile) -> String {
+        file.load().unwrap().config.apps[0].name.clone()
+    }
+
+    #[test]
+    fn saves_keep_backups() {
+        let dir = temp_dir("backups");
+        let file = ConfigFile::new(dir.join("config.json"));
+        assert!(file.load().unwrap().config.apps.is_empty());
+        for i in 0..BACKUPS_KEPT + 3 {
+            file.write(&named(&format!("v{}", i))).unwrap();
+            file.back_up(std::time::UNIX_EPOCH + std::time::Duration::from_secs(i as u64)).unwrap();
+        }
+        // Unchanged since the last backup
+        file.back_up(SystemTime::now()).unwrap();
+        let backups = file.backups();
+        assert_eq!(backups.len(), BACKUPS_KEPT);
+        let (newest, _) = parse(&fs::read(&backups[0]).unwrap()).unwrap();
+        assert_eq!(newest.apps[0].name, format!("v{}", BACKUPS_KEPT + 2));
+        assert!(!dir.join("config.json.tmp").exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn damaged_file_is_recovered_from_newest_backup() {
+        let dir = temp_dir("recover");
+        let file = ConfigFile::new(dir.join("config.json"));
+        file.save(&named("old")).unwrap();
+        file.save(&named("good")).unwrap();
+        fs::write(dir.join("config.json"), "{\"apps\": [{\"id\": ").unwrap();
+
+        let loaded = file.load().unwrap();
+        assert_eq!(loaded.config.apps[0].name, "good");
+        assert!(loaded.recovery.is_some());
+        // load leaves the file alone, recover fixes it
+        assert!(file.load().unwrap().recovery.is_some());
+        let recovery = file.recover().unwrap().recovery.unwrap();
+        assert!(recovery.backup.is_some());
+        assert!(Path::new(&recovery.corrupt_copy.unwrap()).is_file());
+        assert!(file.load().unwrap().recovery.is_none());
+        assert_eq!(app_name(&file), "good");
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn damaged_file_without_backups_starts_empty() {
+        let dir = temp_dir("empty");
+        let file = ConfigFile::new(dir.join("config.json"));
+        fs::write(dir.join("config.json"), "not json").unwrap();
+        let loaded = file.recover().unwrap();
+        assert!(loaded.config.apps.is_empty());
+        assert_eq!(loaded.recovery.unwrap().backup, None);
+        assert!(!dir.join("config.json").exists());
+
+        // Damaged files are never backed up
+        fs::write(dir.join("config.json"), "not json").unwrap();
+        file.save(&named("new")).unwrap();
+        assert_eq!(file.backups().len(), 1);
+        assert_eq!(app_name(&file), "new");
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    fn fixture(json: &str) -> ConfigData {
+        let (config, version) = parse(json.as_bytes()).unwrap();
+        assert_eq!(version, if json.contains("schemaVersion") { SCHEMA_VERSION } else { 0 });
+        assert_eq!(config.schema_version, SCHEMA_VERSION);
+        config
+    }
+
+    #[test]
+    fn baseline_files_drop_runtime_state() {
+        let config = fixture(include_str!("../tests/fixtures/config/v0-baseline.json"));
+        // isRunning is gone
+        assert!(config.apps.iter().all(|app| app.extra.is_empty()));
+        assert_eq!(config.apps[0].environment_vars.as_deref(), Some("RUST_LOG=info\nDATABASE_URL=postgres://localhost/app"));
+        assert_eq!(config.apps[1].icon.as_deref(), Some("🌐"));
+        let settings = config.settings.unwrap();
+        assert!(!settings.minimize_to_tray);
+        assert_eq!(settings.theme, Theme::Light);
+        assert!(config.groups.is_empty());
+    }
+
+    #[test]
+    fn restart_and_log_settings_are_kept() {
+        let config = fixture(include_str!("../tests/fixtures/config/v0-restart-logs.json"));
+        let app = &config.apps[0];
+        assert_eq!(app.restart_policy.as_deref(), Some("on-failure"));
+        assert_eq!((app.stop_timeout, app.max_restarts), (Some(10), Some(5)));
+        assert_eq!(app.encoding.as_deref(), Some("1258"));
+        assert_eq!(config.system_processes.unwrap()["windows"].len(), 2);
+        assert_eq!(config.logs.unwrap().max_runs, 20);
+    }
+
+    #[test]
+    fn triggers_and_health_are_kept() {
+        let config = fixture(include_str!("../tests/fixtures/config/v0-triggers-health.json"));
+        let backend = &config.apps[1];
+        assert_eq!(backend.environment_vars.as_deref(), Some("PORT=3000"));
+        assert_eq!(backend.triggers.as_ref().unwrap().len(), 3);
+        assert!(backend.health.as_ref().unwrap().restart_on_failure);
+        assert_eq!(backend.depends_on.as_deref(), Some(&["lx7p1a9z3y".to_string()][..]));
+        assert_eq!(config.dependency_graph()["lx7p2b8x4w"], ["lx7p1a9z3y"]);
+    }
+
+    #[test]
+    fn groups_are_kept() {
+        let config = fixture(include_str!("../tests/fixtures/config/v0-groups.json"));
+        assert_eq!(config.groups.len(), 2);
+        assert_eq!(config.groups[0].apps, ["lx9c4d5e6f", "lx9c7g8h9i"]);
+        assert!(config.groups[1].apps.is_empty());
+    }
+
+    #[test]
+    fn unknown_fields_survive_a_save() {
+        let json = include_str!("../tests/fixtures/config/v1-unknown-fields.json");
+        let config = fixture(json);
+        let saved = serde_json::to_value(&config).unwrap();
+        let original: Value = serde_json::from_str(json).unwrap();
+        assert_eq!(saved["windowState"], original["windowState"]);
+        assert_eq!(saved["apps"][0]["schedule"], original["apps"][0]["schedule"]);
+        assert_eq!(saved["groups"][0]["collapsed"], true);
+        assert_eq!(saved["settings"]["language"], "vi");
+        // ...and inside nested settings
+        assert_eq!(saved["apps"][0]["triggers"][0]["notify"], "desktop");
+        assert_eq!(saved["apps"][0]["health"]["jitterSecs"], 2);
+        assert_eq!(saved["apps"][0]["health"]["readiness"], original["apps"][0]["health"]["readiness"]);
+        assert_eq!(saved["logs"]["compress"], "gzip");
+    }
+
+    #[test]
+    fn migrations_run_once() {
+        let mut value: Value = serde_json::from_str(r#"{"apps":[{"id":"a","environmentVars":"A=1\nB","isRunning":true}]}"#).unwrap();
+        assert_eq!(migrate(&mut value), Ok(0));
+        let upgraded = value.clone();
+        assert_eq!(migrate(&mut value), Ok(SCHEMA_VERSION));
+        assert_eq!(value, upgraded);
+        assert_eq!(value["apps"][0], serde_json::json!({"id": "a", "environmentVars": "A=1"}));
+        assert!(migrate(&mut Value::Array(Vec::new())).is_err());
+    }
+
+    #[test]
+    fn newer_files_are_not_downgraded() {
+        let json = br#"{"schemaVersion":99,"apps":[{"id":"a","name":"A","executablePath":"x","appType":"shell","isRunning":true}]}"#;
+        let (config, version) = parse(json).unwrap();
+        assert_eq!(version, 99);
+        // A newer schema may give isRunning a meaning, so it is kept
+        assert_eq!(config.apps[0].extra["isRunning"], true);
+        assert_eq!(serde_json::to_value(&config).unwrap()["schemaVersion"], 99);
+    }
+
+    #[test]
+    fn unreadable_newer_files_are_left_alone() {
+        let dir = temp_dir("newer");
+        let file = ConfigFile::new(dir.join("config.json"));
+        file.save(&named("old")).unwrap();
+        let json = r#"{"schemaVersion":99,"apps":[],"settings":{"theme":"solarized"}}"#;
+        fs::write(dir.join("config.json"), json).unwrap();
+        assert!(matches!(file.load(), Err(ConfigError::Io(_))));
+        assert!(file.recover().is_err());
+        assert!(file.save(&named("old")).is_err());
+        assert_eq!(fs::read_to_string(dir.join("config.json")).unwrap(), json);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn old_files_are_upgraded_on_disk() {
+        let dir = temp_dir("migrate");
+        let file = ConfigFile::new(dir.join("config.json"));
+        let json = include_str!("../tests/fixtures/config/v0-baseline.json");
+        fs::write(dir.join("config.json"), json).unwrap();
+        let loaded = file.recover().unwrap();
+        assert_eq!(loaded.version, 0);
+        assert!(loaded.recovery.is_none());
+        assert_eq!(file.load().unwrap().version, SCHEMA_VERSION);
+        // The file as it was is among the backups
+        let backups = file.backups();
+        assert_eq!(fs::read_to_string(backups.last().unwrap()).unwrap(), json);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn diff_reports_ids() {
+        let old = ConfigData {
+            apps: vec![app("a", r#"{"executablePath":"x","appType":"shell"}"#), app("b", r#"{"executablePath":"y","appType":"shell"}"#)],
+            ..Default::default()
+        };
+        assert!(old.diff(&old.clone()).is_empty());
+
+        let mut new = old.clone();
+        new.apps.remove(0);
+        new.apps[0].arguments = Some("--verbose".into());
+        new.apps.push(app("c", r#"{"executablePath":"z","appType":"shell"}"#));
+        new.groups.push(groups::GroupConfig { id: "g".into(), name: "Stack".into(), apps: vec!["c".into()], extra: Map::new() });
+        new.settings = Some(serde_json::from_str(r#"{"theme":"light"}"#).unwrap());
+        let diff = old.diff(&new);
+        assert_eq!((diff.added_apps, diff.removed_apps, diff.changed_apps), (vec!["c".into()], vec!["a".into()], vec!["b".into()]));
+        assert_eq!(diff.added_groups, ["g"]);
+        assert!(diff.settings_changed);
+    }
+
+    #[test]
+    fn saving_the_empty_config_changes_nothing() {
+        let dir = temp_dir("save-empty");
+        let file = ConfigFile::new(dir.join("config.json"));
+        let empty = file.recover().unwrap().config;
+        let sent: ConfigData = serde_json::from_str(r#"{"apps":[]}"#).unwrap();
+        assert!(empty.diff(&sent).is_empty());
+        file.save(&sent).unwrap();
+        assert!(empty.diff(&file.load().unwrap().config).is_empty());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn errors_serialize_with_fields() {
+        let error = ConfigError::Invalid(vec![FieldError {
+            app_id: Some("a".into()),
+            group_id: None,
+            field: "name",
+            message: "Name is required".into(),
+        }]);
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({"kind": "invalid", "message": "Name is required", "errors": [{"appId": "a", "field": "name", "message": "Name is required"}]})
+        );
+    }
+}
@@ -0,0 +1,199 @@
+// Start-order dependencies between managed apps.
+//
+// Each app lists the ids it depends on. Starting an app starts its
+// dependencies first; stopping one stops the apps that depend on it
+// first. Kept free of Tauri types so the ordering and cycle rules can be
+// tested on their own.
+
+use std::collections::{HashMap, HashSet};
+
+/// App id → ids of the apps it depends on.
+pub type Graph = HashMap<String, Vec<String>>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DependencyError {
+    Unknown { app: String, dependency: String },
+    /// The ids around the cycle, ending with the first one again.
+    Cycle(Vec<String>),
+}
+
+impl DependencyError {
+    /// The error with app ids replaced by their names.
+    pub fn describe(&self, names: &HashMap<String, String>) -> String {
+        let name = |id: &String| names.get(id).unwrap_or(id).clone();
+        match self {
+            DependencyError::Unknown { app, dependency } => {
+                format!("{} depends on an app that no longer exists ({})", name(app), dependency)
+            }
+            DependencyError::Cycle(ids) => {
+                format!("Dependency cycle: {}", ids.iter().map(name).collect::<Vec<_>>().join(" → "))
+            }
+        }
+    }
+}
+
+/// Rejects dependencies on missing apps and cycles.
+pub fn check(graph: &Graph) -> Result<(), DependencyError> {
+    for id in sorted_ids(graph) {
+        if let Some(dependency) = graph[id].iter().find(|dep| !graph.contains_key(*dep)) {
+            return Err(DependencyError::Unknown { app: id.clone(), dependency: dependency.clone() });
+        }
+    }
+    topological_order(graph).map(drop)
+}
+
+/// The apps `app_id` needs, dependencies before their dependents, without
+/// `app_id` itself.
+pub fn start_order(graph: &Graph, app_id: &str) -> Result<Vec<String>, DependencyError> {
+    let mut walk = Walk::default();
+    walk.visit(graph, app_id)?;
+    walk.order.pop();
+    Ok(walk.order)
+}
+
+/// `app_ids` and everything they need, in levels that only depend on
+/// earlier levels. The apps of one level can be started in parallel.
+pub fn start_levels(graph: &Graph, app_ids: &[String]) -> Result<Vec<Vec<String>>, DependencyError> {
+    let mut walk = Walk::default();
+    for id in app_ids {
+        walk.visit(graph, id)?;
+    }
+    let mut depth: HashMap<&str, usize> = HashMap::new();
+    let mut levels: Vec<Vec<String>> = Vec::new();
+    // Dependencies come first in the walk, so theirs are known by now
+    for id in &walk.order {
+        let level = graph
+            .get(id)
+            .into_iter()
+            .flatten()
+            .map(|dep| depth[dep.as_str()] + 1)
+            .max()
+            .unwrap_or(0);
+        depth.insert(id, level);
+        if levels.len() <= level {
+            levels.resize(level + 1, Vec::new());
+        }
+        levels[level].push(id.clone());
+    }
+    Ok(levels)
+}
+
+/// The apps that need `app_id`, directly or not, in the order they should
+/// be stopped: dependents before their dependencies.
+pub fn stop_order(graph: &Graph, app_id: &str) -> Result<Vec<String>, DependencyError> {
+    let mut dependents = HashSet::new();
+    let mut queue = vec![app_id];
+    while let Some(current) = queue.pop() {
+        for id in sorted_ids(graph) {
+            if graph[id].iter().any(|dep| dep == current) && dependents.insert(id.as_str()) {
+                queue.push(id);
+            }
+        }
+    }
+    let mut order = topological_order(graph)?;
+    order.retain(|id| dependents.contains(id.as_str()) && id != app_id);
+    order.reverse();
+    Ok(order)
+}
+
+/// Every app, dependencies first.
+fn topological_order(graph: &Graph) -> Result<Vec<String>, DependencyError> {
+    let mut walk = Walk::default();
+    for id in sorted_ids(graph) {
+        walk.visit(graph, id)?;
+    }
+    Ok(walk.order)
+}
+
+/// Sorted so errors and orders don't depend on HashMap order.
+fn sorted_ids(graph: &Graph) -> Vec<&String> {
+    let mut ids: Vec<_> = graph.keys().collect();
+    ids.sort();
+    ids
+}
+
+#[derive(Default)]
+struct Walk {
+    /// The path from the starting app to the one being visited.
+    path: Vec<String>,
+    done: HashSet<String>,
+    order: Vec<String>,
+}
+
+impl Walk {
+    fn visit(&mut self, graph: &Graph, id: &str) -> Result<(), DependencyError> {
+        if self.done.contains(id) {
+            return Ok(());
+        }
+        if let Some(pos) = self.path.iter().position(|p| p == id) {
+            let mut cycle = self.path[pos..].to_vec();
+            cycle.push(id.to_string());
+            return Err(DependencyError::Cycle(cycle));
+        }
+        self.path.push(id.to_string());
+        for dep in graph.get(id).into_iter().flatten() {
+            self.visit(graph, dep)?;
+        }
+        self.path.pop();
+        self.done.insert(id.to_string());
+        self.order.push(id.to_string());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(edges: &[(&str, &[&str])]) -> Graph {
+        edges
+            .iter()
+            .map(|(id, deps)| (id.to_string(), deps.iter().map(|d| d.to_string()).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn dependencies_start_first() {
+        let g = graph(&[("web", &["api"]), ("api", &["db", "cache"]), ("db", &[]), ("cache", &[]), ("other", &[])]);
+        assert_eq!(start_order(&g, "web").unwrap(), ["db", "cache", "api"]);
+        assert_eq!(start_order(&g, "db").unwrap(), Vec::<String>::new());
+        assert!(check(&g).is_ok());
+    }
+
+    #[test]
+    fn shared_dependencies_start_once() {
+        let g = graph(&[("a", &["b", "c"]), ("b", &["d"]), ("c", &["d"]), ("d", &[])]);
+        assert_eq!(start_order(&g, "a").unwrap(), ["d", "b", "c"]);
+    }
+
+    #[test]
+    fn independent_apps_share_a_level() {
+        let g = graph(&[("web", &["api"]), ("api", &["db"]), ("worker", &["db"]), ("db", &[]), ("docs", &[])]);
+        let ids = ["web", "worker", "docs"].map(String::from);
+        assert_eq!(start_levels(&g, &ids).unwrap(), [vec!["db", "docs"], vec!["api", "worker"], vec!["web"]]);
+    }
+
+    #[test]
+    fn dependents_stop_first() {
+        let g = graph(&[("web", &["api"]), ("api", &["db"]), ("worker", &["db"]), ("db", &[]), ("other", &[])]);
+        assert_eq!(stop_order(&g, "db").unwrap(), ["worker", "web", "api"]);
+        assert_eq!(stop_order(&g, "web").unwrap(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn cycles_are_rejected() {
+        let g = graph(&[("a", &["b"]), ("b", &["c"]), ("c", &["a"])]);
+        let err = check(&g).unwrap_err();
+        assert_eq!(err, DependencyError::Cycle(vec!["a".into(), "b".into(), "c".into(), "a".into()]));
+        let names = HashMap::from([("a".to_string(), "DB".to_string())]);
+        assert_eq!(err.describe(&names), "Dependency cycle: DB → b → c → DB");
+        assert!(start_order(&g, "b").is_err());
+        assert!(check(&graph(&[("a", &["a"])])).is_err());
+    }
+
+    #[test]
+    fn unknown_dependencies_are_rejected() {
+        let g = graph(&[("a", &["gone"])]);
+        assert_eq!(check(&g), Err(DependencyError::Unknown { app: "a".into(), dependency: "gone".into() }));
+    }
+}
@@ -0,0 +1,107 @@
+// Named groups of apps ("stacks") that are started and stopped together.
+//
+// Kept free of Tauri types so the aggregate status rules can be tested on
+// their own.
+
+use crate::health::HealthState;
+
+/// A group as stored in the config.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupConfig {
+    pub id: String,
+    pub name: String,
+    /// Member app ids.
+    #[serde(default)]
+    pub apps: Vec<String>,
+    /// Keys this version doesn't know, kept as they are.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum GroupState {
+    Stopped,
+    Starting,
+    Ready,
+    /// Some members run, others are stopped.
+    Partial,
+    /// At least one member is unhealthy.
+    Unhealthy,
+}
+
+impl GroupState {
+    /// The worst state among the members wins, so a group is only ready
+    /// when all of them are.
+    pub fn aggregate(members: &[HealthState]) -> Self {
+        let count = |state| members.iter().filter(|&&s| s == state).count();
+        if count(HealthState::Unhealthy) > 0 {
+            GroupState::Unhealthy
+        } else if count(HealthState::Stopped) == members.len() {
+            GroupState::Stopped
+        } else if count(HealthState::Starting) > 0 {
+            GroupState::Starting
+        } else if count(HealthState::Ready) == members.len() {
+            GroupState::Ready
+        } else {
+            GroupState::Partial
+        }
+    }
+
+    /// Status dot for the tray menu.
+    pub fn icon(&self) -> &'static str {
+        match self {
+            GroupState::Stopped => "🔴",
+            GroupState::Starting => "🟡",
+            GroupState::Ready => "🟢",
+            GroupState::Partial | GroupState::Unhealthy => "🟠",
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupStatus {
+    pub id: String,
+    pub state: GroupState,
+    /// Members that are not stopped.
+    pub running: usize,
+    pub total: usize,
+}
+
+impl GroupStatus {
+    pub fn new(group: &GroupConfig, members: &[HealthState]) -> Self {
+        Self {
+            id: group.id.clone(),
+            state: GroupState::aggregate(members),
+            running: members.iter().filter(|&&s| s != HealthState::Stopped).count(),
+            total: members.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use HealthState::*;
+
+    #[test]
+    fn aggregate_state() {
+        assert_eq!(GroupState::aggregate(&[]), GroupState::Stopped);
+        assert_eq!(GroupState::aggregate(&[Stopped, Stopped]), GroupState::Stopped);
+        assert_eq!(GroupState::aggregate(&[Ready, Ready]), GroupState::Ready);
+        assert_eq!(GroupState::aggregate(&[Ready, Starting]), GroupState::Starting);
+        assert_eq!(GroupState::aggregate(&[Ready, Stopped]), GroupState::Partial);
+        assert_eq!(GroupState::aggregate(&[Starting, Unhealthy, Stopped]), GroupState::Unhealthy);
+    }
+
+    #[test]
+    fn status_counts_running_members() {
+        let group: GroupConfig = serde_json::from_str(r#"{"id":"g","name":"Stack"}"#).unwrap();
+        assert!(group.apps.is_empty());
+        let status = GroupStatus::new(&group, &[Ready, Stopped, Starting]);
+        assert_eq!((status.running, status.total), (2, 3));
+        assert_eq!(status.state, GroupState::Starting);
+    }
+}
@@ -0,0 +1,355 @@
+// Readiness and liveness checks for managed apps.
+//
+// An app is `starting` until its readiness probe passes, then `ready`
+// until its liveness probe fails often enough in a row to make it
+// `unhealthy`. Apps without probes are ready as soon as they are spawned.
+// Kept free of Tauri types, like restart.rs, so the probes and the state
+// machine can be tested on their own.
+
+use regex::Regex;
+use serde_json::{Map, Value};
+use std::io::{Read, Write};
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(10);
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(2);
+const DEFAULT_FAILURE_THRESHOLD: u32 = 3;
+/// How long an app may take to pass its readiness probe before it is
+/// reported unhealthy. It keeps being probed after that.
+const DEFAULT_START_TIMEOUT: Duration = Duration::from_secs(60);
+/// Readiness is polled faster than liveness so `ready` shows up promptly.
+pub const READINESS_POLL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum Probe {
+    /// Something accepts connections on the port.
+    Tcp {
+        port: u16,
+        /// Keys this version doesn't know, kept as they are.
+        #[serde(flatten)]
+        extra: Map<String, Value>,
+    },
+    /// GET http://localhost:port/path answers 2xx.
+    Http {
+        port: u16,
+        #[serde(default)]
+        path: String,
+        #[serde(flatten)]
+        extra: Map<String, Value>,
+    },
+    /// The app printed a matching line; as a liveness probe, since the
+    /// previous check.
+    Log {
+        pattern: String,
+        #[serde(flatten)]
+        extra: Map<String, Value>,
+    },
+}
+
+/// Health settings as sent by the frontend / stored on AppConfig.
+#[derive(Debug, Clone, Default, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthOptions {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub readiness: Option<Probe>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub liveness: Option<Probe>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+    /// Consecutive liveness failures before the app is unhealthy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failure_threshold: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_timeout_secs: Option<u64>,
+    /// Restart the app once liveness makes it unhealthy.
+    #[serde(default)]
+    pub restart_on_failure: bool,
+    /// Keys this version doesn't know, kept as they are.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HealthState {
+    Starting,
+    Ready,
+    Unhealthy,
+    Stopped,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthStatus {
+    pub state: HealthState,
+    /// Why the app is unhealthy: the last probe error.
+    pub reason: Option<String>,
+}
+
+impl HealthStatus {
+    pub fn new(state: HealthState) -> Self {
+        Self { state, reason: None }
+    }
+}
+
+/// Counts the lines matching a log probe's pattern.
+#[derive(Debug)]
+pub struct LogProbe {
+    regex: Regex,
+    hits: AtomicU64,
+}
+
+impl LogProbe {
+    pub fn observe(&self, line: &str) {
+        if self.regex.is_match(line) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Check {
+    Tcp(u16),
+    Http(u16, String),
+    Log(Arc<LogProbe>),
+}
+
+impl Check {
+    fn compile(probe: &Probe) -> Result<Self, String> {
+        Ok(match probe {
+            Probe::Tcp { port, .. } => Check::Tcp(*port),
+            Probe::Http { port, path, .. } => {
+                let path = if path.starts_with('/') { path.clone() } else { format!("/{}", path) };
+                Check::Http(*port, path)
+            }
+            Probe::Log { pattern, .. } => {
+                let regex = Regex::new(pattern).map_err(|e| format!("Invalid pattern {:?}: {}", pattern, e))?;
+                Check::Log(Arc::new(LogProbe { regex, hits: AtomicU64::new(0) }))
+            }
+        })
+    }
+
+    pub fn run(&self, timeout: Duration) -> Result<(), String> {
+        match self {
+            Check::Tcp(port) => connect(*port, timeout).map(drop),
+            Check::Http(port, path) => http_get(*port, path, timeout),
+            Check::Log(probe) => match probe.hits.swap(0, Ordering::Relaxed) {
+                0 => Err(format!("No line matching /{}/", probe.regex)),
+                _ => Ok(()),
+            },
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Check::Tcp(port) => format!("port {}", port),
+            Check::Http(port, path) => format!("http://localhost:{}{}", port, path),
+            Check::Log(probe) => format!("log /{}/", probe.regex),
+        }
+    }
+}
+
+/// The compiled probes and timings for one run of an app.
+#[derive(Debug)]
+pub struct HealthChecks {
+    pub readiness: Option<Check>,
+    pub liveness: Option<Check>,
+    pub interval: Duration,
+    pub timeout: Duration,
+    pub failure_threshold: u32,
+    pub start_timeout: Duration,
+    pub restart_on_failure: bool,
+}
+
+impl HealthChecks {
+    pub fn compile(options: &HealthOptions) -> Result<Self, String> {
+        let secs = |value: Option<u64>, default: Duration| value.filter(|&s| s > 0).map_or(default, Duration::from_secs);
+        Ok(Self {
+            readiness: options.readiness.as_ref().map(Check::compile).transpose()?,
+            liveness: options.liveness.as_ref().map(Check::compile).transpose()?,
+            interval: secs(options.interval_secs, DEFAULT_INTERVAL),
+            timeout: secs(options.timeout_secs, DEFAULT_TIMEOUT),
+            failure_threshold: options.failure_threshold.filter(|&n| n > 0).unwrap_or(DEFAULT_FAILURE_THRESHOLD),
+            start_timeout: secs(options.start_timeout_secs, DEFAULT_START_TIMEOUT),
+            restart_on_failure: options.restart_on_failure,
+        })
+    }
+
+    /// The log probes, which have to see every line the app prints.
+    pub fn log_probes(&self) -> Vec<Arc<LogProbe>> {
+        [&self.readiness, &self.liveness]
+            .into_iter()
+            .filter_map(|check| match check {
+                Some(Check::Log(probe)) => Some(probe.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Probe results in, state changes out.
+#[derive(Debug)]
+pub struct HealthMachine {
+    status: HealthStatus,
+    failures: u32,
+    threshold: u32,
+}
+
+impl HealthMachine {
+    pub fn new(threshold: u32) -> Self {
+        Self { status: HealthStatus::new(HealthState::Starting), failures: 0, threshold }
+    }
+
+    pub fn status(&self) -> &HealthStatus {
+        &self.status
+    }
+
+    /// Returns the new status if the state changed.
+    pub fn passed(&mut self) -> Option<&HealthStatus> {
+        self.failures = 0;
+        self.set(HealthState::Ready, None)
+    }
+
+    /// A failed liveness probe; the app turns unhealthy after `threshold`
+    /// of them in a row.
+    pub fn failed(&mut self, reason: String) -> Option<&HealthStatus> {
+        self.failures += 1;
+        if self.failures < self.threshold {
+            return None;
+        }
+        self.set(HealthState::Unhealthy, Some(reason))
+    }
+
+    /// Not ready within the start timeout.
+    pub fn timed_out(&mut self, reason: String) -> Option<&HealthStatus> {
+        self.set(HealthState::Unhealthy, Some(reason))
+    }
+
+    fn set(&mut self, state: HealthState, reason: Option<String>) -> Option<&HealthStatus> {
+        if self.status.state == state {
+            return None;
+        }
+        self.status = HealthStatus { state, reason };
+        Some(&self.status)
+    }
+}
+
+/// Apps listen on either loopback address, or on all interfaces.
+fn connect(port: u16, timeout: Duration) -> Result<TcpStream, String> {
+    let mut last_error = None;
+    for ip in [Ipv4Addr::LOCALHOST.into(), Ipv6Addr::LOCALHOST.into()] {
+        match TcpStream::connect_timeout(&SocketAddr::new(ip, port), timeout) {
+            Ok(stream) => return Ok(stream),
+            Err(e) => last_error = Some(e),
+        }
+    }
+    Err(format!("Port {}: {}", port, last_error.map(|e| e.to_string()).unwrap_or_default()))
+}
+
+fn http_get(port: u16, path: &str, timeout: Duration) -> Result<(), String> {
+    let mut stream = connect(port, timeout)?;
+    let _ = stream.set_read_timeout(Some(timeout));
+    let _ = stream.set_write_timeout(Some(timeout));
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: localhost:{}\r\nUser-Agent: AppCtrl\r\nConnection: close\r\n\r\n",
+        path, port
+    );
+    stream.write_all(request.as_bytes()).map_err(|e| format!("HTTP request failed: {}", e))?;
+
+    // Only the status line matters
+    let mut head = Vec::new();
+    let mut buf = [0u8; 256];
+    while !head.contains(&b'\n') && head.len() < 1024 {
+        match stream.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => head.extend_from_slice(&buf[..n]),
+            Err(e) => return Err(format!("HTTP request failed: {}", e)),
+        }
+    }
+    let head = String::from_utf8_lossy(&head);
+    let status_line = head.lines().next().unwrap_or_default();
+    match status_line.split_whitespace().nth(1).and_then(|code| code.parse::<u16>().ok()) {
+        Some(code) if (200..300).contains(&code) => Ok(()),
+        Some(code) => Err(format!("HTTP {}", code)),
+        None => Err(format!("Not an HTTP response: {:?}", status_line)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    /// A one-request server answering with `status`.
+    fn serve_once(status: &'static str) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = stream.read(&mut buf);
+            let _ = write!(stream, "HTTP/1.1 {}\r\nContent-Length: 0\r\n\r\n", status);
+        });
+        port
+    }
+
+    #[test]
+    fn state_machine() {
+        let mut machine = HealthMachine::new(2);
+        assert_eq!(machine.status().state, HealthState::Starting);
+        assert_eq!(machine.passed().map(|s| s.state), Some(HealthState::Ready));
+        assert_eq!(machine.passed(), None);
+        assert_eq!(machine.failed("HTTP 503".into()), None);
+        let status = machine.failed("HTTP 503".into()).unwrap();
+        assert_eq!(status.state, HealthState::Unhealthy);
+        assert_eq!(status.reason.as_deref(), Some("HTTP 503"));
+        assert_eq!(machine.failed("HTTP 503".into()), None);
+        assert_eq!(machine.passed().map(|s| s.state), Some(HealthState::Ready));
+        // A pass resets the count
+        assert_eq!(machine.failed("x".into()), None);
+    }
+
+    #[test]
+    fn tcp_and_http_probes() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        assert_eq!(Check::Tcp(port).run(DEFAULT_TIMEOUT), Ok(()));
+        drop(listener);
+        assert!(Check::Tcp(port).run(DEFAULT_TIMEOUT).is_err());
+
+        let port = serve_once("204 No Content");
+        let ok = Check::compile(&Probe::Http { port, path: "health".into(), extra: Map::new() }).unwrap();
+        assert_eq!(ok.describe(), format!("http://localhost:{}/health", port));
+        assert_eq!(ok.run(DEFAULT_TIMEOUT), Ok(()));
+        let failing = Check::Http(serve_once("503 Service Unavailable"), "/".into());
+        assert_eq!(failing.run(DEFAULT_TIMEOUT), Err("HTTP 503".to_string()));
+    }
+
+    #[test]
+    fn log_probes_count_lines_since_the_last_check() {
+        let options: HealthOptions =
+            serde_json::from_str(r#"{"readiness":{"kind":"log","pattern":"Listening on"},"liveness":{"kind":"tcp","port":80}}"#)
+                .unwrap();
+        let checks = HealthChecks::compile(&options).unwrap();
+        assert_eq!(checks.failure_threshold, DEFAULT_FAILURE_THRESHOLD);
+        let probes = checks.log_probes();
+        assert_eq!(probes.len(), 1);
+        let readiness = checks.readiness.as_ref().unwrap();
+        assert!(readiness.run(DEFAULT_TIMEOUT).is_err());
+        probes[0].observe("Listening on :8080");
+        assert_eq!(readiness.run(DEFAULT_TIMEOUT), Ok(()));
+        assert!(readiness.run(DEFAULT_TIMEOUT).is_err());
+    }
+
+    #[test]
+    fn invalid_patterns_are_rejected() {
+        let options = HealthOptions { liveness: Some(Probe::Log { pattern: "(".into(), extra: Map::new() }), ..HealthOptions::default() };
+        assert!(HealthChecks::compile(&options).is_err());
+    }
+}
@@ -0,0 +1,489 @@
+// Helpers for turning an AppConfig entry into a command line.
+
+use std::process::Command;
+
+/// Split the `arguments` field of an app into individual arguments.
+///
+/// Rules:
+/// - Whitespace separates arguments.
+/// - `"..."` groups text, `\"` and `\\` inside it are escapes.
+/// - `'...'` groups text literally, no escapes.
+/// - Outside quotes `\` only escapes a quote or whitespace, so Windows
+///   paths like `C:\tools\bin` and `\\server\share` stay intact.
+/// - `""` or `''` produces an empty argument.
+pub fn split_args(input: &str) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    // True once the current token has started (needed for empty quoted args)
+    let mut in_token = false;
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if in_token {
+                    args.push(std::mem::take(&mut current));
+                    in_token = false;
+                }
+            }
+            '"' => {
+                in_token = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.peek() {
+                            Some('"') | Some('\\') => current.push(chars.next().unwrap()),
+                            _ => current.push('\\'),
+                        },
+                        Some(c) => current.push(c),
+                        None => return Err("Unterminated double quote in arguments".to_string()),
+                    }
+                }
+            }
+            '\'' => {
+                in_token = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => current.push(c),
+                        None => return Err("Unterminated single quote in arguments".to_string()),
+                    }
+                }
+            }
+            '\\' => {
+                in_token = true;
+                match chars.peek() {
+                    Some(&n) if n == '"' || n == '\'' || n.is_whitespace() => {
+                        current.push(n);
+                        chars.next();
+                    }
+                    _ => current.push('\\'),
+                }
+            }
+            c => {
+                in_token = true;
+                current.push(c);
+            }
+        }
+    }
+
+    if in_token {
+        args.push(current);
+    }
+    Ok(args)
+}
+
+/// Parse the `environmentVars` field of an app: one `KEY=value` per line,
+/// blank lines ignored, whitespace around key and value trimmed.
+pub fn parse_env(input: &str) -> Result<Vec<(String, String)>, String> {
+    let mut vars = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(format!("Line {}: expected KEY=value", i + 1));
+        };
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(format!("Line {}: missing variable name", i + 1));
+        }
+        if key.contains(char::is_whitespace) || key.contains('\0') {
+            return Err(format!("Line {}: invalid variable name {:?}", i + 1, key));
+        }
+        vars.push((key.to_string(), value.trim().to_string()));
+    }
+    Ok(vars)
+}
+
+/// Quote an argument for a `cmd.exe /C` command line.
+///
+/// Follows the MSVC runtime rules (backslashes are only special before a
+/// quote) but writes embedded quotes as `""` so cmd's own quote tracking
+/// stays in sync and `&`, `|`, `<`, `>` inside the argument are not
+/// treated as operators.
+pub fn quote_cmd_arg(arg: &str) -> String {
+    let needs_quotes = arg.is_empty()
+        || arg.chars().any(|c| c.is_whitespace() || "\"&|<>^()%!,;=".contains(c));
+    if !needs_quotes {
+        return arg.to_string();
+    }
+
+    let mut out = String::with_capacity(arg.len() + 2);
+    out.push('"');
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        match c {
+            '\\' => backslashes += 1,
+            '"' => {
+                out.extend(std::iter::repeat_n('\\', backslashes * 2));
+                backslashes = 0;
+                out.push_str("\"\"");
+            }
+            c => {
+                out.extend(std::iter::repeat_n('\\', backslashes));
+                backslashes = 0;
+                out.push(c);
+            }
+        }
+    }
+    // Backslashes before the closing quote must be doubled
+    out.extend(std::iter::repeat_n('\\', backslashes * 2));
+    out.push('"');
+    out
+}
+
+/// Join arguments into a `cmd.exe` command line fragment.
+#[cfg_attr(not(windows), allow(dead_code))]
+pub fn join_cmd_args(args: &[String]) -> String {
+    args.iter()
+        .map(|a| quote_cmd_arg(a))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Script passed to `sh -c` so extra arguments arrive as `"$@"`.
+///
+/// The arguments themselves are passed as separate process arguments after
+/// `$0`, so no shell quoting of user input is needed.
+pub fn sh_script(path: &str, has_args: bool) -> String {
+    if has_args {
+        format!("{} \"$@\"", path)
+    } else {
+        path.to_string()
+    }
+}
+
+/// Interpreter used for `shell` apps when none is configured.
+pub fn default_shell() -> &'static str {
+    if cfg!(windows) {
+        "cmd"
+    } else {
+        "sh"
+    }
+}
+
+/// Build the command for an app according to its `app_type`.
+///
+/// - `exe`: the executable is spawned directly, so the child PID is the app.
+/// - `bat`: run through `cmd.exe` (Windows only).
+/// - `shell` (and anything unknown): run through `shell`, which may be
+///   `sh`, `bash`, `zsh`, `pwsh`/`powershell`, `cmd` or a path to one of them.
+pub fn build_command(
+    app_type: &str,
+    path: &str,
+    args: &[String],
+    shell: Option<&str>,
+) -> Result<Command, String> {
+    match app_type {
+        "exe" => {
+            let mut c = Command::new(path);
+            c.args(args);
+            Ok(c)
+        }
+        "bat" => {
+            if cfg!(windows) {
+                Ok(cmd_command(&quote_cmd_arg(path), args))
+            } else {
+                Err("Batch files can only be run on Windows".to_string())
+            }
+        }
+        _ => {
+            let shell = shell.map(str::trim).filter(|s| !s.is_empty()).unwrap_or(default_shell());
+            Ok(shell_command(shell, path, args))
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum ShellKind {
+    Posix,
+    PowerShell,
+    Cmd,
+}
+
+fn shell_kind(shell: &str) -> ShellKind {
+    // Split on both separators so Windows paths work on every host
+    let file = shell.rsplit(['/', '\\']).next().unwrap_or(shell).to_lowercase();
+    let name = file.strip_suffix(".exe").unwrap_or(&file);
+    match name {
+        "pwsh" | "powershell" => ShellKind::PowerShell,
+        "cmd" => ShellKind::Cmd,
+        _ => ShellKind::Posix,
+    }
+}
+
+fn shell_command(shell: &str, path: &str, args: &[String]) -> Command {
+    // A path to an existing script is run as a file; anything else is
+    // treated as a command line for the interpreter.
+    let is_script = std::path::Path::new(path).is_file();
+    match shell_kind(shell) {
+        ShellKind::Posix => {
+            let mut c = Command::new(shell);
+            if is_script {
+                c.arg(path);
+            } else {
+                c.args(["-c", &sh_script(path, !args.is_empty()), shell]);
+            }
+            c.args(args);
+            c
+        }
+        ShellKind::PowerShell => {
+            let mut c = Command::new(shell);
+            c.args(["-NoLogo", "-NoProfile"]);
+            if is_script {
+                c.args(["-ExecutionPolicy", "Bypass", "-File", path]);
+                c.args(args);
+            } else {
+                let mut line = path.to_string();
+                for a in args {
+                    line.push(' ');
+                    line.push_str(&quote_pwsh_arg(a));
+                }
+                c.args(["-Command", &line]);
+            }
+            c
+        }
+        ShellKind::Cmd => {
+            if is_script {
+                cmd_command(&quote_cmd_arg(path), args)
+            } else {
+                cmd_command(path, args)
+            }
+        }
+    }
+}
+
+/// `cmd.exe /S /C "<command_line> <args>"`.
+#[cfg(windows)]
+fn cmd_command(command_line: &str, args: &[String]) -> Command {
+    use std::os::windows::process::CommandExt;
+
+    let mut c = Command::new("cmd.exe");
+    let mut full_cmd = command_line.to_string();
+    if !args.is_empty() {
+        full_cmd.push(' ');
+        full_cmd.push_str(&join_cmd_args(args));
+    }
+    // /S makes cmd strip only the outer quotes, so quoted args survive
+    c.raw_arg(format!("/S /C \"{}\"", full_cmd));
+    c
+}
+
+#[cfg(not(windows))]
+fn cmd_command(command_line: &str, args: &[String]) -> Command {
+    let mut c = Command::new("cmd");
+    c.args(["/C", command_line]);
+    c.args(args);
+    c
+}
+
+/// Quote an argument as a PowerShell single-quoted string.
+fn quote_pwsh_arg(arg: &str) -> String {
+    if !arg.is_empty() && arg.chars().all(|c| c.is_ascii_alphanumeric() || "-_./:=\\".contains(c)) {
+        return arg.to_string();
+    }
+    format!("'{}'", arg.replace('\'', "''"))
+}
+
+/// Quote an argument the way the MSVC runtime splits a command line.
+fn quote_windows_arg(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains([' ', '\t', '"']) {
+        return arg.to_string();
+    }
+    let mut out = String::with_capacity(arg.len() + 2);
+    out.push('"');
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        if c == '\\' {
+            backslashes += 1;
+        } else {
+            if c == '"' {
+                // Double the backslashes before it, then escape the quote
+                out.extend(std::iter::repeat_n('\\', backslashes + 1));
+            }
+            backslashes = 0;
+        }
+        out.push(c);
+    }
+    out.extend(std::iter::repeat_n('\\', backslashes));
+    out.push('"');
+    out
+}
+
+/// `cmd` as a single Windows command line, for starting it without
+/// `std::process` (terminal mode). The arguments of `cmd.exe` commands
+/// built by `cmd_command` are already a raw command line.
+#[cfg_attr(not(windows), allow(dead_code))]
+pub fn windows_command_line(cmd: &Command) -> String {
+    let program = cmd.get_program().to_string_lossy();
+    let is_cmd = program.eq_ignore_ascii_case("cmd.exe");
+    let mut line = quote_windows_arg(&program);
+    for arg in cmd.get_args() {
+        let arg = arg.to_string_lossy();
+        line.push(' ');
+        if is_cmd {
+            line.push_str(&arg);
+        } else {
+            line.push_str(&quote_windows_arg(&arg));
+        }
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(s: &str) -> Vec<String> {
+        split_args(s).unwrap()
+    }
+
+    #[test]
+    fn splits_on_whitespace() {
+        assert_eq!(split("  --port 8080\t-v  "), ["--port", "8080", "-v"]);
+        assert!(split("").is_empty());
+        assert!(split("   ").is_empty());
+    }
+
+    #[test]
+    fn double_quotes_group_and_escape() {
+        assert_eq!(split(r#"--name "hello world""#), ["--name", "hello world"]);
+        assert_eq!(split(r#""say \"hi\"""#), [r#"say "hi""#]);
+        assert_eq!(split(r#""C:\Program Files\App\\""#), [r"C:\Program Files\App\"]);
+        assert_eq!(split(r#""a\\b""#), [r"a\b"]);
+    }
+
+    #[test]
+    fn single_quotes_are_literal() {
+        assert_eq!(split(r#"'it "is" \n'"#), [r#"it "is" \n"#]);
+    }
+
+    #[test]
+    fn quotes_join_adjacent_text() {
+        assert_eq!(split(r#"--opt="a b"'c d'e"#), ["--opt=a bc de"]);
+    }
+
+    #[test]
+    fn empty_arguments_are_kept() {
+        assert_eq!(split(r#"a "" b ''"#), ["a", "", "b", ""]);
+    }
+
+    #[test]
+    fn backslashes_outside_quotes() {
+        assert_eq!(split(r"C:\tools\bin \\server\share"), [r"C:\tools\bin", r"\\server\share"]);
+        assert_eq!(split(r"one\ arg two\'s"), ["one arg", "two's"]);
+    }
+
+    #[test]
+    fn unterminated_quotes_are_errors() {
+        assert!(split_args(r#"--name "oops"#).is_err());
+        assert!(split_args("'oops").is_err());
+    }
+
+    #[test]
+    fn env_lines() {
+        let vars = parse_env("PORT=8080\n\n  NODE_ENV = production \nEMPTY=\nURL=a=b").unwrap();
+        let vars: Vec<_> = vars.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+        assert_eq!(vars, [("PORT", "8080"), ("NODE_ENV", "production"), ("EMPTY", ""), ("URL", "a=b")]);
+        assert_eq!(parse_env("A=1\nnonsense").unwrap_err(), "Line 2: expected KEY=value");
+        assert!(parse_env("=value").is_err());
+        assert!(parse_env("MY VAR=1").is_err());
+    }
+
+    #[test]
+    fn cmd_quoting() {
+        assert_eq!(quote_cmd_arg("plain"), "plain");
+        assert_eq!(quote_cmd_arg(""), "\"\"");
+        assert_eq!(quote_cmd_arg("a b"), "\"a b\"");
+        assert_eq!(quote_cmd_arg("a&b"), "\"a&b\"");
+        assert_eq!(quote_cmd_arg(r#"say "hi""#), r#""say ""hi""""#);
+        assert_eq!(quote_cmd_arg(r"C:\dir with space\"), r#""C:\dir with space\\""#);
+        assert_eq!(quote_cmd_arg(r#"a\"b c"#), r#""a\\""b c""#);
+    }
+
+    #[test]
+    fn cmd_join() {
+        let args = split(r#"--title "My App" "" x"#);
+        assert_eq!(join_cmd_args(&args), r#"--title "My App" "" x"#);
+    }
+
+    #[test]
+    fn sh_script_forwards_args() {
+        assert_eq!(sh_script("./run.sh", false), "./run.sh");
+        assert_eq!(sh_script("./run.sh", true), "./run.sh \"$@\"");
+    }
+
+    fn argv(c: &Command) -> Vec<String> {
+        std::iter::once(c.get_program())
+            .chain(c.get_args())
+            .map(|s| s.to_string_lossy().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn exe_is_spawned_directly() {
+        let args = split(r#"--title "My App""#);
+        let c = build_command("exe", "/opt/My Tools/app", &args, None).unwrap();
+        assert_eq!(argv(&c), ["/opt/My Tools/app", "--title", "My App"]);
+    }
+
+    #[test]
+    fn shell_kinds() {
+        assert_eq!(shell_kind("sh"), ShellKind::Posix);
+        assert_eq!(shell_kind("/usr/bin/zsh"), ShellKind::Posix);
+        assert_eq!(shell_kind("pwsh"), ShellKind::PowerShell);
+        assert_eq!(shell_kind(r"C:\Windows\System32\WindowsPowerShell\v1.0\powershell.exe"), ShellKind::PowerShell);
+        assert_eq!(shell_kind("CMD.EXE"), ShellKind::Cmd);
+    }
+
+    #[test]
+    fn posix_shell_command_line() {
+        let args = split("a 'b c'");
+        let c = build_command("shell", "npm run dev", &args, Some("bash")).unwrap();
+        assert_eq!(argv(&c), ["bash", "-c", "npm run dev \"$@\"", "bash", "a", "b c"]);
+    }
+
+    #[test]
+    fn posix_shell_script_file() {
+        let script = std::env::temp_dir().join("appctrl launch test.sh");
+        std::fs::write(&script, "echo hi").unwrap();
+        let path = script.to_string_lossy().to_string();
+        let c = build_command("shell", &path, &["x".to_string()], Some("zsh")).unwrap();
+        let _ = std::fs::remove_file(&script);
+        assert_eq!(argv(&c), ["zsh".to_string(), path, "x".to_string()]);
+    }
+
+    #[test]
+    fn powershell_command_line() {
+        let args = split(r#"-Name "it's here""#);
+        let c = build_command("shell", "Get-Item", &args, Some("pwsh")).unwrap();
+        assert_eq!(
+            argv(&c),
+            ["pwsh", "-NoLogo", "-NoProfile", "-Command", "Get-Item -Name 'it''s here'"]
+        );
+    }
+
+    #[test]
+    fn blank_shell_uses_default() {
+        let c = build_command("shell", "echo hi", &[], Some("  ")).unwrap();
+        assert_eq!(c.get_program().to_string_lossy(), if cfg!(windows) { "cmd.exe" } else { "sh" });
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn bat_requires_windows() {
+        assert!(build_command("bat", "run.bat", &[], None).is_err());
+    }
+
+    #[test]
+    fn windows_command_lines() {
+        let mut c = Command::new(r"C:\Program Files\app.exe");
+        c.args(["plain", "two words", r#"say "hi""#, r"C:\my dir\", r"C:\dir\", ""]);
+        assert_eq!(
+            windows_command_line(&c),
+            r#""C:\Program Files\app.exe" plain "two words" "say \"hi\"" "C:\my dir\\" C:\dir\ """#
+        );
+    }
+}
@@ -1,278 +1,1386 @@
-use std::collections::HashMap;
-use std::io::{BufRead, BufReader};
-use std::process::{Child, Command, Stdio};
-use std::sync::Mutex;
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::{mpsc, Arc, Condvar, Mutex};
+use std::time::{Duration, Instant, SystemTime};
 use tauri::{
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
-    menu::{Menu, MenuItem, Submenu},
+    menu::{IsMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu},
     AppHandle, Emitter, Manager,
 };
 
 #[cfg(windows)]
 use std::os::windows::process::CommandExt;
 
+mod launch;
+#[cfg_attr(not(windows), allow(dead_code))]
+mod netstat;
+mod process;
+mod logs;
+mod health;
+mod capture;
+mod ansi;
+mod pty;
+#[cfg(target_os = "linux")]
+mod procfs;
+#[cfg(target_os = "linux")]
+mod procnet;
+mod restart;
+mod triggers;
+mod deps;
+mod groups;
+mod config;
+
+use config::{AppConfig, ConfigData, ConfigError};
+
 struct ProcessManager {
-    processes: Mutex<HashMap<String, Child>>,
+    processes: Mutex<HashMap<String, RunningApp>>,
+    // Apps in the middle of stop_app; their exit is reported by stop_app
+    stopping: Mutex<HashSet<String>>,
+    // Apps launch_app is spawning; another start waits for them instead
+    starting: Mutex<HashSet<String>>,
+    restarts: Mutex<HashMap<String, restart::RestartTracker>>,
+    outputs: Mutex<HashMap<String, Arc<Mutex<AppOutput>>>>,
+    // Last size the log view asked for, reused when a terminal app restarts
+    terminal_sizes: Mutex<HashMap<String, pty::PtySize>>,
+    // Lines matched by an error trigger, until reset from the frontend
+    error_counts: Mutex<HashMap<String, u64>>,
+    health: Mutex<HashMap<String, AppHealth>>,
 }
 
 impl ProcessManager {
     fn new() -> Self {
         Self {
             processes: Mutex::new(HashMap::new()),
+            stopping: Mutex::new(HashSet::new()),
+            starting: Mutex::new(HashSet::new()),
+            restarts: Mutex::new(HashMap::new()),
+            outputs: Mutex::new(HashMap::new()),
+            terminal_sizes: Mutex::new(HashMap::new()),
+            error_counts: Mutex::new(HashMap::new()),
+            health: Mutex::new(HashMap::new()),
+        }
+    }
+    
+    fn is_running(&self, app_id: &str) -> bool {
+        let processes = self.processes.lock().unwrap();
+        // Waiting to be restarted still counts as running
+        processes.contains_key(app_id)
+            || self.restarts.lock().unwrap().get(app_id).is_some_and(|t| t.is_pending())
+    }
+    
+    fn output(&self, app_id: &str) -> Arc<Mutex<AppOutput>> {
+        self.outputs
+            .lock()
+            .unwrap()
+            .entry(app_id.to_string())
+            .or_insert_with(|| Arc::new(Mutex::new(AppOutput::new())))
+            .clone()
+    }
+}
+
+/// Lines kept in memory per app for `get_app_logs`.
+const OUTPUT_BUFFER_LINES: usize = 5000;
+
+/// Output of one app, kept across its runs.
+struct AppOutput {
+    recent: logs::LogBuffer,
+    // Id and log file of the current (or last) run
+    run_id: Option<String>,
+    file: Option<logs::LogWriter>,
+}
+
+impl AppOutput {
+    fn new() -> Self {
+        Self {
+            recent: logs::LogBuffer::new(OUTPUT_BUFFER_LINES),
+            run_id: None,
+            file: None,
         }
     }
 }
 
+/// A spawned app. The `Child` itself belongs to the exit watcher thread,
+/// which blocks in `wait()` and publishes the result through `exit`.
+struct RunningApp {
+    pid: u32,
+    exit: Arc<ExitWatch>,
+    // The terminal, or the stdin pipe of apps started with keep_stdin
+    input: Option<Arc<Mutex<Box<dyn Write + Send>>>>,
+    terminal: Option<Arc<dyn pty::PtyControl>>,
+}
+
+/// Health of the app's latest run, the one `exit` watches.
+struct AppHealth {
+    exit: Arc<ExitWatch>,
+    status: health::HealthStatus,
+}
+
+#[derive(Default)]
+struct ExitWatch {
+    info: Mutex<Option<process::ExitInfo>>,
+    reaped: Condvar,
+}
+
+impl ExitWatch {
+    fn finish(&self, info: process::ExitInfo) {
+        *self.info.lock().unwrap() = Some(info);
+        self.reaped.notify_all();
+    }
+
+    fn is_finished(&self) -> bool {
+        self.info.lock().unwrap().is_some()
+    }
+
+    fn wait_timeout(&self, timeout: Duration) -> Option<process::ExitInfo> {
+        let info = self.info.lock().unwrap();
+        let (info, _) = self.reaped.wait_timeout_while(info, timeout, |info| info.is_none()).unwrap();
+        info.clone()
+    }
+}
+
+const DEFAULT_STOP_TIMEOUT_SECS: u64 = 5;
+/// How long to wait for the process to be reaped after the forced kill.
+const KILL_WAIT: Duration = Duration::from_secs(5);
+
+/// Everything needed to launch an app again when its restart policy kicks in.
+#[derive(Clone)]
+struct LaunchSpec {
+    path: String,
+    app_type: String,
+    working_dir: String,
+    args: String,
+    env_vars: String,
+    shell: Option<String>,
+    encoding: Option<String>,
+    force_color: bool,
+    terminal: bool,
+    keep_stdin: bool,
+    triggers: Vec<triggers::TriggerRule>,
+    health: health::HealthOptions,
+}
+
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 async fn start_app(
     app_handle: AppHandle,
     app_id: String,
     path: String,
-    _app_type: String,
+    app_type: String,
     working_dir: String,
-    _args: String,
+    args: String,
     env_vars: String,
+    shell: Option<String>,
+    restart: Option<restart::RestartOptions>,
+    encoding: Option<String>,
+    force_color: Option<bool>,
+    terminal: Option<bool>,
+    keep_stdin: Option<bool>,
+    triggers: Option<Vec<triggers::TriggerRule>>,
+    health: Option<health::HealthOptions>,
 ) -> Result<(), String> {
     let state = app_handle.state::<ProcessManager>();
+    if state.processes.lock().unwrap().contains_key(&app_id) {
+        return Err("App is already running".to_string());
+    }
+    
+    let spec = LaunchSpec {
+        path,
+        app_type,
+        working_dir,
+        args,
+        env_vars,
+        shell,
+        encoding,
+        force_color: force_color.unwrap_or(false),
+        terminal: terminal.unwrap_or(false),
+        keep_stdin: keep_stdin.unwrap_or(false),
+        triggers: triggers.unwrap_or_default(),
+        health: health.unwrap_or_default(),
+    };
+    start_dependencies(&app_handle, &app_id).await?;
+    if !launch_app(&app_handle, &app_id, &restart.unwrap_or_default(), &spec)? {
+        return Err("App is already running".to_string());
+    }
+    Ok(())
+}
+
+/// Start one app, leaving its dependencies alone. `Ok(false)` if it is
+/// already running or being started by someone else.
+fn launch_app(
+    app_handle: &AppHandle,
+    app_id: &str,
+    restart: &restart::RestartOptions,
+    spec: &LaunchSpec,
+) -> Result<bool, String> {
+    let state = app_handle.state::<ProcessManager>();
     
     {
         let processes = state.processes.lock().unwrap();
-        if processes.contains_key(&app_id) {
-            return Err("App is already running".to_string());
+        // Claimed under the processes lock so two starts can't both spawn
+        if processes.contains_key(app_id) || !state.starting.lock().unwrap().insert(app_id.to_string()) {
+            return Ok(false);
         }
     }
     
-    let mut cmd = if cfg!(windows) {
-        let mut c = Command::new("cmd.exe");
-        // Run chcp 65001 (UTF-8) before the actual command
-        let full_cmd = format!("chcp 65001 >nul && {}", path);
-        c.args(["/C", &full_cmd]);
-        c
-    } else {
-        let mut c = Command::new("sh");
-        c.args(["-c", &path]);
-        c
+    // A fresh tracker also cancels a restart still waiting out its backoff
+    let tracker = restart::RestartTracker::new(restart);
+    state.restarts.lock().unwrap().insert(app_id.to_string(), tracker);
+    
+    let result = spawn_app(app_handle, app_id, spec);
+    if result.is_err() {
+        state.restarts.lock().unwrap().remove(app_id);
+    }
+    state.starting.lock().unwrap().remove(app_id);
+    result.map(|()| true)
+}
+
+/// Start what the app depends on, as saved in the config, each one ready
+/// before the next is started.
+async fn start_dependencies(app_handle: &AppHandle, app_id: &str) -> Result<(), String> {
+    let config = saved_config(app_handle);
+    let order = deps::start_order(&config.dependency_graph(), app_id).map_err(|e| e.describe(&config.app_names()))?;
+    let state = app_handle.state::<ProcessManager>();
+    for dep_id in order {
+        let dep = config
+            .apps
+            .iter()
+            .find(|app| app.id == dep_id)
+            .ok_or_else(|| format!("Dependency {} no longer exists", dep_id))?;
+        if !state.processes.lock().unwrap().contains_key(&dep_id) {
+            emit_system(app_handle, app_id, &format!("⏳ Starting dependency {}", dep.name));
+            // Another dependent may start it first; then it is only waited for
+            launch_app(app_handle, &dep_id, &dep.restart_options(), &dep.launch_spec())
+                .map_err(|e| format!("Could not start dependency {}: {}", dep.name, e))?;
+        }
+        let app_handle_wait = app_handle.clone();
+        tauri::async_runtime::spawn_blocking(move || wait_until_ready(&app_handle_wait, &dep_id))
+            .await
+            .map_err(|e| e.to_string())?
+            .map_err(|e| format!("Dependency {} {}", dep.name, e))?;
+    }
+    Ok(())
+}
+
+/// Block until the app's health check passes. Apps without a readiness
+/// probe are ready once spawned; with one, the probe's start timeout
+/// bounds the wait.
+fn wait_until_ready(app_handle: &AppHandle, app_id: &str) -> Result<(), String> {
+    let state = app_handle.state::<ProcessManager>();
+    loop {
+        // Until its spawn is done the health is still the last run's
+        if state.starting.lock().unwrap().contains(app_id) {
+            std::thread::sleep(DEPENDENCY_POLL);
+            continue;
+        }
+        let status = state.health.lock().unwrap().get(app_id).map(|app| app.status.clone());
+        match status {
+            Some(status) => match status.state {
+                health::HealthState::Ready => return Ok(()),
+                health::HealthState::Starting => {}
+                health::HealthState::Unhealthy => {
+                    return Err(format!("is unhealthy: {}", status.reason.unwrap_or_default()));
+                }
+                health::HealthState::Stopped => return Err("stopped before it was ready".to_string()),
+            },
+            None => return Err("is not running".to_string()),
+        }
+        std::thread::sleep(DEPENDENCY_POLL);
+    }
+}
+
+/// How often a dependency's health is looked at while waiting for it.
+const DEPENDENCY_POLL: Duration = Duration::from_millis(200);
+
+/// Spawn the app, forward its output and watch for it to exit.
+fn spawn_app(app_handle: &AppHandle, app_id: &str, spec: &LaunchSpec) -> Result<(), String> {
+    let state = app_handle.state::<ProcessManager>();
+    open_run_log(app_handle, app_id);
+    
+    let args = match launch::split_args(&spec.args) {
+        Ok(a) => a,
+        Err(e) => {
+            emit_system(app_handle, app_id, &format!("❌ Invalid arguments: {}", e));
+            return Err(format!("Invalid arguments: {}", e));
+        }
+    };
+    
+    let env_vars = match launch::parse_env(&spec.env_vars) {
+        Ok(vars) => vars,
+        Err(e) => {
+            emit_system(app_handle, app_id, &format!("❌ Invalid environment variables: {}", e));
+            return Err(format!("Invalid environment variables: {}", e));
+        }
+    };
+    
+    let encoding = match capture::OutputEncoding::parse(spec.encoding.as_deref().unwrap_or_default()) {
+        Ok(e) => e,
+        Err(e) => {
+            emit_system(app_handle, app_id, &format!("❌ Invalid encoding: {}", e));
+            return Err(format!("Invalid encoding: {}", e));
+        }
+    };
+    
+    let triggers = match triggers::Triggers::compile(&spec.triggers) {
+        Ok(t) => t,
+        Err(e) => {
+            emit_system(app_handle, app_id, &format!("❌ Invalid log trigger: {}", e));
+            return Err(format!("Invalid log trigger: {}", e));
+        }
+    };
+    
+    let checks = match health::HealthChecks::compile(&spec.health) {
+        Ok(c) => c,
+        Err(e) => {
+            emit_system(app_handle, app_id, &format!("❌ Invalid health check: {}", e));
+            return Err(format!("Invalid health check: {}", e));
+        }
     };
+    let log_probes = checks.log_probes();
     
-    if !working_dir.is_empty() {
-        cmd.current_dir(&working_dir);
-        let _ = app_handle.emit("app-output", serde_json::json!({
-            "appId": &app_id,
-            "line": format!("📁 Working dir: {}", working_dir)
-        }));
-    } else if let Some(parent) = std::path::Path::new(&path).parent() {
+    let mut cmd = match launch::build_command(&spec.app_type, &spec.path, &args, spec.shell.as_deref()) {
+        Ok(c) => c,
+        Err(e) => {
+            emit_system(app_handle, app_id, &format!("❌ Failed to start: {}", e));
+            return Err(format!("Failed to start: {}", e));
+        }
+    };
+    
+    if !spec.working_dir.is_empty() {
+        cmd.current_dir(&spec.working_dir);
+        emit_system(app_handle, app_id, &format!("📁 Working dir: {}", spec.working_dir));
+    } else if let Some(parent) = std::path::Path::new(&spec.path).parent() {
         if parent.exists() && !parent.as_os_str().is_empty() {
             cmd.current_dir(parent);
         }
     }
     
-    // Set UTF-8 encoding for proper Unicode support
-    cmd.env("PYTHONIOENCODING", "utf-8");
-    cmd.env("PYTHONUTF8", "1");
-    cmd.env("CHCP", "65001");
+    // Ask Python for UTF-8 unless the output is decoded with a code page
+    if encoding.wants_utf8() {
+        cmd.env("PYTHONIOENCODING", "utf-8");
+        cmd.env("PYTHONUTF8", "1");
+    }
     
-    if !env_vars.is_empty() {
-        for line in env_vars.lines() {
-            let line = line.trim();
-            if !line.is_empty() {
-                if let Some((key, value)) = line.split_once('=') {
-                    cmd.env(key.trim(), value.trim());
-                }
-            }
+    // Output goes to a pipe, so most tools turn colors off unless told
+    // otherwise; the log view renders them from the escape codes
+    if spec.force_color {
+        for (key, value) in [
+            ("FORCE_COLOR", "1"),
+            ("CLICOLOR_FORCE", "1"),
+            ("CARGO_TERM_COLOR", "always"),
+            ("PY_COLORS", "1"),
+        ] {
+            cmd.env(key, value);
         }
     }
     
-    cmd.stdout(Stdio::piped())
-       .stderr(Stdio::piped())
-       .stdin(Stdio::null());
-    
-    #[cfg(windows)]
-    {
-        cmd.creation_flags(0x08000000);
-    }
+    cmd.envs(env_vars);
     
-    let result = cmd.spawn();
+    let result = if spec.terminal {
+        let size = state.terminal_sizes.lock().unwrap().get(app_id).copied();
+        spawn_terminal(cmd, size.unwrap_or(pty::DEFAULT_SIZE))
+    } else {
+        spawn_piped(cmd, spec.keep_stdin)
+    };
     
-    let mut child = match result {
-        Ok(c) => c,
+    let Spawned { mut child, outputs, input, terminal } = match result {
+        Ok(s) => s,
         Err(e) => {
-            let _ = app_handle.emit("app-output", serde_json::json!({
-                "appId": &app_id,
-                "line": format!("❌ Failed to start: {}", e)
-            }));
+            emit_system(app_handle, app_id, &format!("❌ Failed to start: {}", e));
             return Err(format!("Failed to start: {}", e));
         }
     };
     
-    let stdout = child.stdout.take();
-    let stderr = child.stderr.take();
-    
-    let _ = app_handle.emit("app-output", serde_json::json!({
-        "appId": &app_id,
-        "line": format!("✓ Started: {}", path)
-    }));
+    emit_system(app_handle, app_id, &format!("✓ Started: {}", spec.path));
+    // For starts the frontend didn't ask for: the tray, triggers, restarts
+    let _ = app_handle.emit("app-started", serde_json::json!({ "appId": app_id }));
     
+    let exit = Arc::new(ExitWatch::default());
     {
         let mut processes = state.processes.lock().unwrap();
-        processes.insert(app_id.clone(), child);
-    }
-    
-    if let Some(stdout) = stdout {
-        let app_handle_clone = app_handle.clone();
-        let app_id_clone = app_id.clone();
-        std::thread::spawn(move || {
-            let reader = BufReader::new(stdout);
-            for line in reader.lines().flatten() {
-                let _ = app_handle_clone.emit("app-output", serde_json::json!({
-                    "appId": &app_id_clone,
-                    "line": line
-                }));
-            }
+        processes.insert(app_id.to_string(), RunningApp {
+            pid: child.id(),
+            exit: exit.clone(),
+            input: input.map(|input| Arc::new(Mutex::new(input))),
+            terminal: terminal.map(Arc::from),
         });
     }
+    // Published here rather than by the monitor thread, before the exit
+    // watcher starts, so a quick exit's Stopped can't be overwritten and a
+    // dependent waiting for this app never sees the previous run's state
+    let machine = health::HealthMachine::new(checks.failure_threshold);
+    set_health(app_handle, app_id, &exit, machine.status());
     
-    if let Some(stderr) = stderr {
-        let app_handle_clone = app_handle.clone();
-        let app_id_clone = app_id.clone();
-        std::thread::spawn(move || {
-            let reader = BufReader::new(stderr);
-            for line in reader.lines().flatten() {
-                let _ = app_handle_clone.emit("app-output", serde_json::json!({
-                    "appId": &app_id_clone,
-                    "line": format!("[stderr] {}", line)
-                }));
-            }
-        });
+    let (chunks_tx, chunks_rx) = mpsc::channel();
+    for (stream, pipe) in outputs {
+        read_pipe(pipe, stream, chunks_tx.clone());
     }
+    drop(chunks_tx);
+    
+    let app_handle_output = app_handle.clone();
+    let app_id_output = app_id.to_string();
+    let spec_output = spec.clone();
+    std::thread::spawn(move || {
+        forward_output(&app_handle_output, &app_id_output, &spec_output, encoding, triggers, log_probes, chunks_rx);
+    });
+    
+    let app_handle_health = app_handle.clone();
+    let app_id_health = app_id.to_string();
+    let spec_health = spec.clone();
+    let exit_health = exit.clone();
+    std::thread::spawn(move || {
+        monitor_health(&app_handle_health, &app_id_health, &spec_health, &exit_health, machine, checks);
+    });
     
     let app_handle_exit = app_handle.clone();
-    let app_id_exit = app_id.clone();
+    let app_id_exit = app_id.to_string();
+    let spec_exit = spec.clone();
+    let started = Instant::now();
     std::thread::spawn(move || {
-        loop {
-            std::thread::sleep(std::time::Duration::from_millis(500));
-            let state = app_handle_exit.state::<ProcessManager>();
+        let info = match child.wait() {
+            Ok(status) => process::ExitInfo::new(&status, started.elapsed()),
+            Err(_) => process::ExitInfo { code: None, signal: None, runtime: started.elapsed() },
+        };
+        
+        let state = app_handle_exit.state::<ProcessManager>();
+        let reported_by_stop = {
             let mut processes = state.processes.lock().unwrap();
-            
-            if let Some(child) = processes.get_mut(&app_id_exit) {
-                match child.try_wait() {
-                    Ok(Some(status)) => {
-                        let code = status.code().unwrap_or(-1);
-                        let msg = if code == 0 {
-                            "✓ Process exited successfully".to_string()
-                        } else {
-                            format!("⚠ Process exited with code: {}", code)
-                        };
-                        let _ = app_handle_exit.emit("app-output", serde_json::json!({
-                            "appId": &app_id_exit,
-                            "line": msg
-                        }));
-                        let _ = app_handle_exit.emit("app-stopped", serde_json::json!({
-                            "appId": &app_id_exit
-                        }));
-                        processes.remove(&app_id_exit);
+            // The entry may already belong to a newer run of the same app
+            let current = processes
+                .get(&app_id_exit)
+                .is_some_and(|app| Arc::ptr_eq(&app.exit, &exit));
+            if current {
+                processes.remove(&app_id_exit);
+            }
+            !current || state.stopping.lock().unwrap().contains(&app_id_exit)
+        };
+        // Only after the entry is gone, so a start right after stop_app returns works
+        exit.finish(info.clone());
+        set_health(&app_handle_exit, &app_id_exit, &exit, &health::HealthStatus::new(health::HealthState::Stopped));
+        
+        if !reported_by_stop {
+            emit_system(&app_handle_exit, &app_id_exit, &info.describe());
+            handle_exit(&app_handle_exit, &app_id_exit, &spec_exit, &info);
+        }
+    });
+    
+    Ok(())
+}
+
+/// A started app and our ends of its stdio.
+struct Spawned {
+    child: Box<dyn pty::ChildProcess>,
+    outputs: Vec<(logs::LogStream, Box<dyn Read + Send>)>,
+    input: Option<Box<dyn Write + Send>>,
+    terminal: Option<Box<dyn pty::PtyControl>>,
+}
+
+/// With `keep_stdin` the app's stdin stays open for `send_app_input`;
+/// otherwise it reads EOF right away.
+fn spawn_piped(mut cmd: Command, keep_stdin: bool) -> std::io::Result<Spawned> {
+    cmd.stdout(Stdio::piped())
+       .stderr(Stdio::piped())
+       .stdin(if keep_stdin { Stdio::piped() } else { Stdio::null() });
+    
+    // CREATE_NO_WINDOW | CREATE_NEW_PROCESS_GROUP, the group lets stop_app
+    // send Ctrl+Break to the app without hitting AppCtrl itself
+    #[cfg(windows)]
+    {
+        cmd.creation_flags(0x08000000 | 0x00000200);
+    }
+    
+    let mut child = cmd.spawn()?;
+    let mut outputs: Vec<(logs::LogStream, Box<dyn Read + Send>)> = Vec::new();
+    if let Some(stdout) = child.stdout.take() {
+        outputs.push((logs::LogStream::Stdout, Box::new(stdout)));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        outputs.push((logs::LogStream::Stderr, Box::new(stderr)));
+    }
+    let input = child.stdin.take().map(|stdin| Box::new(stdin) as Box<dyn Write + Send>);
+    Ok(Spawned { child: Box::new(child), outputs, input, terminal: None })
+}
+
+/// Terminal mode: stdout and stderr arrive mixed on the one terminal.
+fn spawn_terminal(cmd: Command, size: pty::PtySize) -> std::io::Result<Spawned> {
+    let pty = pty::spawn(cmd, size)?;
+    Ok(Spawned {
+        child: pty.child,
+        outputs: vec![(logs::LogStream::Stdout, pty.output)],
+        input: Some(pty.input),
+        terminal: Some(pty.control),
+    })
+}
+
+/// Pass raw chunks read from one of the app's pipes to `forward_output`
+/// until the pipe closes.
+fn read_pipe<R: Read + Send + 'static>(
+    mut pipe: R,
+    stream: logs::LogStream,
+    chunks: mpsc::Sender<(logs::LogStream, Vec<u8>)>,
+) {
+    std::thread::spawn(move || {
+        let mut buf = [0u8; 8192];
+        loop {
+            match pipe.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => {
+                    if chunks.send((stream, buf[..n].to_vec())).is_err() {
                         break;
                     }
-                    Ok(None) => {}
-                    Err(_) => {
-                        processes.remove(&app_id_exit);
-                        break;
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(_) => break,
+            }
+        }
+    });
+}
+
+/// Turn the app's raw output into lines until both pipes have closed.
+/// Unfinished lines (prompts, progress bars) are shown as partial records
+/// once they have waited `capture::PARTIAL_FLUSH`.
+fn forward_output(
+    app_handle: &AppHandle,
+    app_id: &str,
+    spec: &LaunchSpec,
+    encoding: capture::OutputEncoding,
+    mut triggers: triggers::Triggers,
+    log_probes: Vec<Arc<health::LogProbe>>,
+    chunks: mpsc::Receiver<(logs::LogStream, Vec<u8>)>,
+) {
+    // Complete lines only; a partial one is checked once it is finished
+    let mut emit_complete = |stream: logs::LogStream, line: ansi::StyledText| {
+        let text = (!triggers.is_empty() || !log_probes.is_empty()).then(|| line.text.clone());
+        let seq = emit_line(app_handle, app_id, stream, line, false);
+        if let Some(text) = text {
+            for probe in &log_probes {
+                probe.observe(&text);
+            }
+            for found in triggers.check(&text) {
+                on_trigger(app_handle, app_id, spec, stream, seq, &text, found);
+            }
+        }
+    };
+    let mut streams = [
+        (logs::LogStream::Stdout, capture::LineAssembler::default(), ansi::AnsiParser::default()),
+        (logs::LogStream::Stderr, capture::LineAssembler::default(), ansi::AnsiParser::default()),
+    ];
+    loop {
+        let deadline = streams.iter().filter_map(|(_, lines, _)| lines.deadline()).min();
+        let received = match deadline {
+            Some(deadline) => chunks.recv_timeout(deadline.saturating_duration_since(Instant::now())),
+            None => chunks.recv().map_err(|_| mpsc::RecvTimeoutError::Disconnected),
+        };
+        let now = Instant::now();
+        match received {
+            Ok((stream, bytes)) => {
+                if let Some((_, lines, styles)) = streams.iter_mut().find(|(s, _, _)| *s == stream) {
+                    for line in lines.push(&bytes, now) {
+                        emit_complete(stream, styles.parse(&encoding.decode(&line)));
                     }
                 }
-            } else {
-                break;
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+        for (stream, lines, styles) in streams.iter_mut() {
+            if let Some(partial) = lines.take_partial(now) {
+                // The full line is parsed again once it completes
+                let line = styles.clone().parse(&encoding.decode(&partial));
+                emit_line(app_handle, app_id, *stream, line, true);
+            }
+        }
+    }
+    for (stream, lines, styles) in streams.iter_mut() {
+        if let Some(rest) = lines.finish() {
+            emit_complete(*stream, styles.parse(&encoding.decode(&rest)));
+        }
+    }
+}
+
+/// Report a trigger match as `app-trigger` and run the rule's action.
+fn on_trigger(
+    app_handle: &AppHandle,
+    app_id: &str,
+    spec: &LaunchSpec,
+    stream: logs::LogStream,
+    seq: u64,
+    line: &str,
+    found: triggers::TriggerMatch,
+) {
+    let state = app_handle.state::<ProcessManager>();
+    let error_count = {
+        let mut counts = state.error_counts.lock().unwrap();
+        let count = counts.entry(app_id.to_string()).or_default();
+        if found.is_error {
+            *count += 1;
+        }
+        *count
+    };
+    let _ = app_handle.emit("app-trigger", TriggerEvent {
+        app_id,
+        seq,
+        stream,
+        line,
+        pattern: &found.pattern,
+        captures: &found.captures,
+        action: found.action,
+        error_count,
+    });
+    
+    let app_handle = app_handle.clone();
+    let app_id = app_id.to_string();
+    match found.action {
+        triggers::TriggerAction::None => {}
+        triggers::TriggerAction::Restart => {
+            emit_system(&app_handle, &app_id, &format!("⚡ Log matched /{}/, restarting", found.pattern));
+            let spec = spec.clone();
+            std::thread::spawn(move || restart_now(&app_handle, &app_id, &spec, None));
+        }
+        triggers::TriggerAction::Stop => {
+            emit_system(&app_handle, &app_id, &format!("⚡ Log matched /{}/, stopping", found.pattern));
+            tauri::async_runtime::spawn(async move {
+                let config = find_app_config(&app_handle, &app_id);
+                let stop_signal = config.as_ref().and_then(|c| c.stop_signal.clone());
+                let stop_timeout = config.as_ref().and_then(|c| c.stop_timeout);
+                if let Err(e) = stop_app(app_handle.clone(), app_id.clone(), None, stop_signal, stop_timeout).await {
+                    emit_system(&app_handle, &app_id, &format!("⚠ Trigger could not stop the app: {}", e));
+                }
+            });
+        }
+        triggers::TriggerAction::StartApp => {
+            let Some(target) = found.target_app_id else { return };
+            tauri::async_runtime::spawn(async move {
+                let Some(config) = find_app_config(&app_handle, &target) else {
+                    emit_system(&app_handle, &app_id, &format!("⚠ Trigger target app {} not found", target));
+                    return;
+                };
+                emit_system(&app_handle, &app_id, &format!("⚡ Log matched /{}/, starting {}", found.pattern, config.name));
+                if let Err(e) = config.start(app_handle.clone()).await {
+                    emit_system(&app_handle, &app_id, &format!("⚠ Could not start {}: {}", config.name, e));
+                }
+            });
+        }
+    }
+}
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct TriggerEvent<'a> {
+    app_id: &'a str,
+    seq: u64,
+    stream: logs::LogStream,
+    line: &'a str,
+    pattern: &'a str,
+    captures: &'a [Option<String>],
+    action: triggers::TriggerAction,
+    error_count: u64,
+}
+
+/// Probe the app until it exits: readiness until it passes, then
+/// liveness every interval.
+fn monitor_health(
+    app_handle: &AppHandle,
+    app_id: &str,
+    spec: &LaunchSpec,
+    exit: &Arc<ExitWatch>,
+    mut machine: health::HealthMachine,
+    checks: health::HealthChecks,
+) {
+    if let Some(readiness) = &checks.readiness {
+        let started = Instant::now();
+        loop {
+            match readiness.run(checks.timeout) {
+                Ok(()) => break,
+                Err(e) if started.elapsed() >= checks.start_timeout => {
+                    let reason = format!("Not ready after {}s: {}", checks.start_timeout.as_secs(), e);
+                    if let Some(status) = machine.timed_out(reason.clone()) {
+                        set_health(app_handle, app_id, exit, status);
+                        emit_system(app_handle, app_id, &format!("⚠ Unhealthy: {}", reason));
+                    }
+                }
+                Err(_) => {}
+            }
+            if exit.wait_timeout(health::READINESS_POLL).is_some() {
+                return;
+            }
+        }
+    }
+    if let Some(status) = machine.passed() {
+        set_health(app_handle, app_id, exit, status);
+        if let Some(readiness) = &checks.readiness {
+            emit_system(app_handle, app_id, &format!("♥ Ready ({})", readiness.describe()));
+        }
+    }
+    
+    let Some(liveness) = &checks.liveness else { return };
+    // How long liveness passed before the current failures. A restart
+    // after failures that follow no passing check at all must not count
+    // as a stable run, or the restart tracker never gives up on it.
+    let mut passing_since = None;
+    let mut passed_for = Duration::ZERO;
+    loop {
+        if exit.wait_timeout(checks.interval).is_some() {
+            return;
+        }
+        let changed = match liveness.run(checks.timeout) {
+            Ok(()) => {
+                passing_since.get_or_insert_with(Instant::now);
+                machine.passed()
+            }
+            Err(e) => {
+                if let Some(since) = passing_since.take() {
+                    passed_for = since.elapsed();
+                }
+                machine.failed(e)
+            }
+        };
+        let Some(status) = changed.cloned() else { continue };
+        set_health(app_handle, app_id, exit, &status);
+        match status.state {
+            health::HealthState::Unhealthy => {
+                let reason = status.reason.unwrap_or_default();
+                emit_system(app_handle, app_id, &format!("⚠ Unhealthy: {} ({})", reason, liveness.describe()));
+                if checks.restart_on_failure {
+                    emit_system(app_handle, app_id, "↻ Restarting after failed liveness checks");
+                    restart_now(app_handle, app_id, spec, Some(passed_for));
+                    return;
+                }
+            }
+            _ => emit_system(app_handle, app_id, &format!("♥ Healthy again ({})", liveness.describe())),
+        }
+    }
+}
+
+/// Record the health of the run watched by `exit` and report it as
+/// `app-health`. Late results from an earlier run are dropped.
+fn set_health(app_handle: &AppHandle, app_id: &str, exit: &Arc<ExitWatch>, status: &health::HealthStatus) {
+    let state = app_handle.state::<ProcessManager>();
+    {
+        let mut apps = state.health.lock().unwrap();
+        if status.state != health::HealthState::Starting {
+            let current = apps.get(app_id).is_some_and(|app| Arc::ptr_eq(&app.exit, exit));
+            // A probe that finished just after the exit mustn't undo `stopped`
+            if !current || (exit.is_finished() && status.state != health::HealthState::Stopped) {
+                return;
+            }
+        }
+        apps.insert(app_id.to_string(), AppHealth { exit: exit.clone(), status: status.clone() });
+    }
+    let _ = app_handle.emit("app-health", HealthEvent { app_id, status });
+}
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct HealthEvent<'a> {
+    app_id: &'a str,
+    #[serde(flatten)]
+    status: &'a health::HealthStatus,
+}
+
+/// Stop the app and start it again with the same settings, once its
+/// restart tracker's backoff has passed. Unlike stop_app this keeps the
+/// tracker, which gives up on an app that keeps being restarted.
+/// `healthy_for` is how long the run went well, if not its whole runtime.
+fn restart_now(app_handle: &AppHandle, app_id: &str, spec: &LaunchSpec, healthy_for: Option<Duration>) {
+    let state = app_handle.state::<ProcessManager>();
+    let running = {
+        let processes = state.processes.lock().unwrap();
+        let running = processes.get(app_id).map(|app| (app.pid, app.exit.clone()));
+        if running.is_some() && !state.stopping.lock().unwrap().insert(app_id.to_string()) {
+            return;
+        }
+        running
+    };
+    let Some((pid, exit)) = running else { return };
+    
+    let config = find_app_config(app_handle, app_id);
+    let (signal, timeout) = stop_settings(
+        app_handle,
+        app_id,
+        config.as_ref().and_then(|c| c.stop_signal.as_deref()),
+        config.as_ref().and_then(|c| c.stop_timeout),
+    );
+    let info = graceful_stop(app_handle, app_id, pid, &exit, signal, timeout);
+    state.stopping.lock().unwrap().remove(app_id);
+    // Otherwise still tracked, the exit watcher reports it if it ever goes away
+    let Some(info) = info else { return };
+    
+    let decision = match state.restarts.lock().unwrap().get_mut(app_id) {
+        Some(tracker) => tracker.on_forced_restart(healthy_for.unwrap_or(info.runtime), Instant::now()),
+        None => restart::Decision::Stop,
+    };
+    follow_decision(app_handle, app_id, spec, decision, &info);
+}
+
+/// Send a line to the frontend, the app's recent-output buffer and its
+/// log file.
+fn emit_output(app_handle: &AppHandle, app_id: &str, stream: logs::LogStream, line: &str) {
+    emit_line(app_handle, app_id, stream, ansi::StyledText::plain(line), false);
+}
+
+/// A partial line is replaced by the next line of the same stream and is
+/// left out of the log file. Returns the line's seq.
+fn emit_line(app_handle: &AppHandle, app_id: &str, stream: logs::LogStream, line: ansi::StyledText, partial: bool) -> u64 {
+    let output = app_handle.state::<ProcessManager>().output(app_id);
+    // Held while emitting so events leave in seq order
+    let mut output = output.lock().unwrap();
+    let output = &mut *output;
+    let now = SystemTime::now();
+    if let Some(file) = output.file.as_mut().filter(|_| !partial) {
+        let _ = file.write(now, stream, &line.text);
+    }
+    
+    let record = output.recent.push(now, stream, output.run_id.as_deref(), line, partial);
+    let _ = app_handle.emit("app-output", OutputEvent { app_id, record });
+    record.seq
+}
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct OutputEvent<'a> {
+    app_id: &'a str,
+    #[serde(flatten)]
+    record: &'a logs::LogRecord,
+}
+
+/// Messages from AppCtrl itself rather than the app.
+fn emit_system(app_handle: &AppHandle, app_id: &str, line: &str) {
+    emit_output(app_handle, app_id, logs::LogStream::System, line);
+}
+
+fn log_dir(app_id: &str) -> std::path::PathBuf {
+    let mut path = get_config_path();
+    path.pop();
+    path.push("logs");
+    path.push(app_id);
+    path
+}
+
+/// Start a new log run for the app. Output still reaches the frontend if
+/// the log directory can't be written.
+fn open_run_log(app_handle: &AppHandle, app_id: &str) {
+    let settings = saved_config(app_handle).logs.unwrap_or_default();
+    
+    let run_id = logs::new_run_id(SystemTime::now());
+    let writer = if logs::is_safe_name(app_id) {
+        logs::LogWriter::create(&log_dir(app_id), &run_id, settings).map_err(|e| e.to_string())
+    } else {
+        Err(format!("unsupported app id {:?}", app_id))
+    };
+    
+    let output = app_handle.state::<ProcessManager>().output(app_id);
+    let (file, error) = match writer {
+        Ok(writer) => (Some(writer), None),
+        Err(e) => (None, Some(e)),
+    };
+    {
+        let mut output = output.lock().unwrap();
+        output.run_id = Some(run_id);
+        output.file = file;
+    }
+    if let Some(e) = error {
+        emit_system(app_handle, app_id, &format!("⚠ Log file disabled: {}", e));
+    }
+}
+
+/// Raw input for an app started in terminal mode: typed text, "\r" for
+/// Enter, "\u{3}" for Ctrl+C.
+#[tauri::command]
+fn write_app_stdin(app_handle: AppHandle, app_id: String, data: String) -> Result<(), String> {
+    let (input, _) = app_input(&app_handle, &app_id)?;
+    write_input(&input, data.as_bytes())
+}
+
+/// Send a line such as "reload" to an app's console. The line ending is
+/// added unless `text` already has one.
+#[tauri::command]
+fn send_app_input(app_handle: AppHandle, app_id: String, text: String) -> Result<(), String> {
+    let (input, terminal) = app_input(&app_handle, &app_id)?;
+    let mut data = text;
+    if !data.ends_with('\n') && !data.ends_with('\r') {
+        // A terminal sends "\r" for Enter, a pipe reader expects "\n"
+        data.push(if terminal { '\r' } else { '\n' });
+    }
+    write_input(&input, data.as_bytes())
+}
+
+type AppInput = Arc<Mutex<Box<dyn Write + Send>>>;
+
+/// The app's input and whether it is a terminal.
+fn app_input(app_handle: &AppHandle, app_id: &str) -> Result<(AppInput, bool), String> {
+    let state = app_handle.state::<ProcessManager>();
+    let processes = state.processes.lock().unwrap();
+    let app = processes.get(app_id).ok_or("App is not running")?;
+    let input = app.input.clone().ok_or("App was not started with input enabled")?;
+    Ok((input, app.terminal.is_some()))
+}
+
+fn write_input(input: &AppInput, data: &[u8]) -> Result<(), String> {
+    let mut input = input.lock().unwrap();
+    input.write_all(data).and_then(|_| input.flush()).map_err(|e| match e.kind() {
+        std::io::ErrorKind::BrokenPipe => "App has closed its input".to_string(),
+        _ => format!("Failed to write to app: {}", e),
+    })
+}
+
+/// Where the app is in starting → ready ⇄ unhealthy → stopped.
+#[tauri::command]
+fn get_app_health(app_handle: AppHandle, app_id: String) -> health::HealthStatus {
+    let state = app_handle.state::<ProcessManager>();
+    let apps = state.health.lock().unwrap();
+    match apps.get(&app_id) {
+        Some(app) => app.status.clone(),
+        None => health::HealthStatus::new(health::HealthState::Stopped),
+    }
+}
+
+/// Trigger error counts of all apps that have had a match.
+#[tauri::command]
+fn get_app_error_counts(app_handle: AppHandle) -> HashMap<String, u64> {
+    app_handle.state::<ProcessManager>().error_counts.lock().unwrap().clone()
+}
+
+#[tauri::command]
+fn reset_app_error_count(app_handle: AppHandle, app_id: String) {
+    app_handle.state::<ProcessManager>().error_counts.lock().unwrap().remove(&app_id);
+}
+
+/// Resize the app's terminal to match the log view. The size is kept for
+/// the app's next start as well.
+#[tauri::command]
+fn resize_app_terminal(app_handle: AppHandle, app_id: String, cols: u16, rows: u16) -> Result<(), String> {
+    if cols == 0 || rows == 0 {
+        return Err("Invalid terminal size".to_string());
+    }
+    let size = pty::PtySize { cols, rows };
+    let state = app_handle.state::<ProcessManager>();
+    state.terminal_sizes.lock().unwrap().insert(app_id.clone(), size);
+    
+    let terminal = state.processes.lock().unwrap().get(&app_id).and_then(|app| app.terminal.clone());
+    match terminal {
+        Some(terminal) => terminal.resize(size).map_err(|e| format!("Failed to resize terminal: {}", e)),
+        None => Ok(()),
+    }
+}
+
+/// Buffered output lines after `since_seq`, oldest first. Lets the log view
+/// backfill what it missed before picking up live `app-output` events.
+#[tauri::command]
+fn get_app_logs(
+    app_handle: AppHandle,
+    app_id: String,
+    since_seq: Option<u64>,
+    limit: Option<usize>,
+) -> Vec<logs::LogRecord> {
+    let output = app_handle.state::<ProcessManager>().outputs.lock().unwrap().get(&app_id).cloned();
+    match output {
+        Some(output) => output
+            .lock()
+            .unwrap()
+            .recent
+            .since(since_seq.unwrap_or(0), limit.unwrap_or(OUTPUT_BUFFER_LINES)),
+        None => Vec::new(),
+    }
+}
+
+#[tauri::command]
+fn list_app_log_runs(app_id: String) -> Result<Vec<logs::RunSummary>, String> {
+    if !logs::is_safe_name(&app_id) {
+        return Err("Invalid app id".to_string());
+    }
+    logs::list_runs(&log_dir(&app_id)).map_err(|e| e.to_string())
+}
+
+/// Lines `start..start + limit` of one logged run, the latest by default.
+#[tauri::command]
+fn read_app_log(
+    app_id: String,
+    run_id: Option<String>,
+    start: Option<usize>,
+    limit: Option<usize>,
+) -> Result<logs::LogPage, String> {
+    if !logs::is_safe_name(&app_id) || !run_id.as_deref().is_none_or(logs::is_safe_name) {
+        return Err("Invalid app or run id".to_string());
+    }
+    logs::read_run(&log_dir(&app_id), run_id.as_deref(), start.unwrap_or(0), limit.unwrap_or(1000))
+        .map_err(|e| e.to_string())
+}
+
+/// Apply the app's restart policy after it exited on its own.
+fn handle_exit(app_handle: &AppHandle, app_id: &str, spec: &LaunchSpec, exit: &process::ExitInfo) {
+    let state = app_handle.state::<ProcessManager>();
+    let decision = match state.restarts.lock().unwrap().get_mut(app_id) {
+        Some(tracker) => tracker.on_exit(exit.success(), exit.runtime, Instant::now()),
+        None => restart::Decision::Stop,
+    };
+    follow_decision(app_handle, app_id, spec, decision, exit);
+}
+
+/// Restart the app after the backoff, give up on it or leave it stopped,
+/// as its restart tracker decided after the run that ended with `exit`.
+fn follow_decision(
+    app_handle: &AppHandle,
+    app_id: &str,
+    spec: &LaunchSpec,
+    decision: restart::Decision,
+    exit: &process::ExitInfo,
+) {
+    let state = app_handle.state::<ProcessManager>();
+    match decision {
+        restart::Decision::Restart { attempt, delay } => {
+            let max_restarts = state.restarts.lock().unwrap().get(app_id).and_then(|t| t.max_restarts());
+            let limit = max_restarts.map(|max| format!("/{}", max)).unwrap_or_default();
+            let line = format!("↻ Restarting in {}s (attempt {}{})", delay.as_secs(), attempt, limit);
+            emit_system(app_handle, app_id, &line);
+            let _ = app_handle.emit("app-restarting", serde_json::json!({
+                "appId": app_id,
+                "attempt": attempt,
+                "delayMs": delay.as_millis() as u64
+            }));
+            std::thread::sleep(delay);
+            
+            // stop_app or a manual start_app during the backoff drops or replaces the tracker
+            let claimed = state.restarts.lock().unwrap().get_mut(app_id).is_some_and(|t| t.take_pending());
+            if !claimed {
+                return;
+            }
+            // Settings saved since the last start apply to the restart too
+            let spec = find_app_config(app_handle, app_id).map(|app| app.launch_spec()).unwrap_or_else(|| spec.clone());
+            if spawn_app(app_handle, app_id, &spec).is_err() {
+                state.restarts.lock().unwrap().remove(app_id);
+                emit_stopped(app_handle, app_id, Some(exit));
+            }
+        }
+        restart::Decision::GiveUp(reason) => {
+            state.restarts.lock().unwrap().remove(app_id);
+            emit_system(app_handle, app_id, &format!("⛔ {}, giving up", reason));
+            let _ = app_handle.emit("app-crash-loop", serde_json::json!({
+                "appId": app_id,
+                "reason": reason.kind(),
+                "message": reason.to_string()
+            }));
+            emit_stopped(app_handle, app_id, Some(exit));
+        }
+        restart::Decision::Stop => {
+            state.restarts.lock().unwrap().remove(app_id);
+            emit_stopped(app_handle, app_id, Some(exit));
+        }
+    }
+}
+
+/// `app-stopped`, with the exit details when the process was reaped here.
+fn emit_stopped(app_handle: &AppHandle, app_id: &str, exit: Option<&process::ExitInfo>) {
+    let mut payload = serde_json::json!({ "appId": app_id });
+    if let Some(exit) = exit {
+        payload["exitCode"] = serde_json::json!(exit.code);
+        payload["signal"] = serde_json::json!(exit.signal);
+        payload["signalName"] = serde_json::json!(exit.signal_name());
+        payload["runtimeMs"] = serde_json::json!(exit.runtime.as_millis() as u64);
+    }
+    let _ = app_handle.emit("app-stopped", payload);
+}
+
+/// Stop the app, after the running apps that depend on it.
+#[tauri::command]
+async fn stop_app(
+    app_handle: AppHandle,
+    app_id: String,
+    exe_path: Option<String>,
+    stop_signal: Option<String>,
+    stop_timeout: Option<u64>,
+) -> Result<(), String> {
+    stop_dependents(&app_handle, &app_id).await;
+    stop_one(app_handle, app_id, exe_path, stop_signal, stop_timeout).await
+}
+
+/// Stop the running apps that need this one, dependents first.
+async fn stop_dependents(app_handle: &AppHandle, app_id: &str) {
+    let apps = saved_apps(app_handle);
+    let Ok(order) = deps::stop_order(&saved_config(app_handle).dependency_graph(), app_id) else { return };
+    let state = app_handle.state::<ProcessManager>();
+    for id in order {
+        if !state.processes.lock().unwrap().contains_key(&id) {
+            continue;
+        }
+        let Some(dependent) = apps.iter().find(|app| app.id == id) else { continue };
+        emit_system(app_handle, app_id, &format!("■ Stopping {} first, it depends on this app", dependent.name));
+        let result = stop_one(app_handle.clone(), id.clone(), None, dependent.stop_signal.clone(), dependent.stop_timeout).await;
+        if let Err(e) = result {
+            emit_system(app_handle, &id, &format!("⚠ {}", e));
+        }
+    }
+}
+
+/// Start every app of the group and what they need. Apps that don't
+/// need each other start in parallel, each level once the one before it
+/// is ready.
+#[tauri::command]
+async fn start_group(app_handle: AppHandle, group_id: String) -> Result<(), String> {
+    let config = saved_config(&app_handle);
+    let group = find_group(&config, &group_id)?;
+    let levels = deps::start_levels(&config.dependency_graph(), &group.apps).map_err(|e| e.describe(&config.app_names()))?;
+    let apps = config.apps;
+    for level in levels {
+        let tasks: Vec<_> = level
+            .iter()
+            .filter_map(|id| apps.iter().find(|app| &app.id == id).cloned())
+            .map(|app| {
+                let app_handle = app_handle.clone();
+                tauri::async_runtime::spawn_blocking(move || start_member(&app_handle, &app))
+            })
+            .collect();
+        let mut errors = Vec::new();
+        for task in tasks {
+            if let Err(e) = task.await.map_err(|e| e.to_string()).and_then(|result| result) {
+                errors.push(e);
+            }
+        }
+        // The next level needs this one
+        if !errors.is_empty() {
+            return Err(errors.join("; "));
+        }
+    }
+    Ok(())
+}
+
+/// Launch the app unless it already runs, and wait for it to be ready.
+fn start_member(app_handle: &AppHandle, app: &AppConfig) -> Result<(), String> {
+    // Already running or started by someone else is fine, it is waited for
+    launch_app(app_handle, &app.id, &app.restart_options(), &app.launch_spec())
+        .map_err(|e| format!("{}: {}", app.name, e))?;
+    wait_until_ready(app_handle, &app.id).map_err(|e| format!("{} {}", app.name, e))
+}
+
+/// Stop the running apps of the group, and the apps outside it that
+/// depend on them, dependents first. Apps that don't need each other
+/// stop in parallel.
+#[tauri::command]
+async fn stop_group(app_handle: AppHandle, group_id: String) -> Result<(), String> {
+    let config = saved_config(&app_handle);
+    let group = find_group(&config, &group_id)?;
+    let graph = config.dependency_graph();
+    let mut targets: HashSet<String> = group.apps.iter().cloned().collect();
+    for id in &group.apps {
+        targets.extend(deps::stop_order(&graph, id).unwrap_or_default());
+    }
+    let ids: Vec<String> = targets.iter().cloned().collect();
+    let levels = deps::start_levels(&graph, &ids).map_err(|e| e.describe(&config.app_names()))?;
+    let apps = config.apps;
+    let state = app_handle.state::<ProcessManager>();
+    let mut errors = Vec::new();
+    for level in levels.into_iter().rev() {
+        let tasks: Vec<_> = level
+            .iter()
+            .filter(|id| targets.contains(*id) && state.processes.lock().unwrap().contains_key(*id))
+            .filter_map(|id| apps.iter().find(|app| &app.id == id))
+            .map(|app| {
+                let stop = stop_one(app_handle.clone(), app.id.clone(), None, app.stop_signal.clone(), app.stop_timeout);
+                (app.name.clone(), tauri::async_runtime::spawn(stop))
+            })
+            .collect();
+        for (name, task) in tasks {
+            if let Err(e) = task.await.map_err(|e| e.to_string()).and_then(|result| result) {
+                errors.push(format!("{}: {}", name, e));
             }
         }
-    });
-    
-    Ok(())
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("; "))
+    }
 }
 
+/// Aggregate status of every saved group.
 #[tauri::command]
-async fn stop_app(app_handle: AppHandle, app_id: String, exe_path: Option<String>) -> Result<(), String> {
+fn get_group_statuses(app_handle: AppHandle) -> Vec<groups::GroupStatus> {
+    let state = app_handle.state::<ProcessManager>();
+    group_statuses(&state, &saved_config(&app_handle).groups)
+}
+
+fn group_statuses(state: &ProcessManager, groups: &[groups::GroupConfig]) -> Vec<groups::GroupStatus> {
+    let health = state.health.lock().unwrap();
+    groups
+        .iter()
+        .map(|group| {
+            let members: Vec<_> = group
+                .apps
+                .iter()
+                .map(|id| health.get(id).map_or(health::HealthState::Stopped, |app| app.status.state))
+                .collect();
+            groups::GroupStatus::new(group, &members)
+        })
+        .collect()
+}
+
+/// Stop one app, leaving the apps that depend on it alone.
+async fn stop_one(
+    app_handle: AppHandle,
+    app_id: String,
+    exe_path: Option<String>,
+    stop_signal: Option<String>,
+    stop_timeout: Option<u64>,
+) -> Result<(), String> {
     let state = app_handle.state::<ProcessManager>();
     
-    let mut processes = state.processes.lock().unwrap();
-    if let Some(mut child) = processes.remove(&app_id) {
-        #[cfg(windows)]
-        {
-            let pid = child.id();
-            let _ = Command::new("taskkill")
-                .args(["/F", "/T", "/PID", &pid.to_string()])
-                .creation_flags(0x08000000)
-                .output();
+    // A user stop is final, whatever the restart policy says
+    let restart_pending = state
+        .restarts
+        .lock()
+        .unwrap()
+        .remove(&app_id)
+        .is_some_and(|tracker| tracker.is_pending());
+    
+    let running = {
+        let processes = state.processes.lock().unwrap();
+        let running = processes.get(&app_id).map(|app| (app.pid, app.exit.clone()));
+        // Marked under the processes lock so the exit watcher can't miss it
+        if running.is_some() && !state.stopping.lock().unwrap().insert(app_id.clone()) {
+            return Err("App is already stopping".to_string());
         }
+        running
+    };
+    if let Some((pid, exit)) = running {
+        let (signal, timeout) = stop_settings(&app_handle, &app_id, stop_signal.as_deref(), stop_timeout);
+        
+        let app_handle_stop = app_handle.clone();
+        let app_id_stop = app_id.clone();
+        let info = tauri::async_runtime::spawn_blocking(move || {
+            graceful_stop(&app_handle_stop, &app_id_stop, pid, &exit, signal, timeout)
+        }).await.ok().flatten();
+        state.stopping.lock().unwrap().remove(&app_id);
         
-        let _ = child.kill();
-        let _ = app_handle.emit("app-output", serde_json::json!({
-            "appId": &app_id,
-            "line": "■ Process stopped by user"
-        }));
-        let _ = app_handle.emit("app-stopped", serde_json::json!({
-            "appId": &app_id
-        }));
+        let Some(info) = info else {
+            // Still tracked, the exit watcher reports it if it ever goes away
+            return Err(format!("Process {} did not exit", pid));
+        };
+        emit_system(&app_handle, &app_id, "■ Process stopped by user");
+        emit_stopped(&app_handle, &app_id, Some(&info));
+        Ok(())
+    } else if restart_pending {
+        emit_system(&app_handle, &app_id, "■ Restart cancelled by user");
+        emit_stopped(&app_handle, &app_id, None);
         Ok(())
     } else {
-        // Try to kill by executable name if provided
+        // Try to kill by executable name if provided. Windows only, as
+        // elsewhere names like python3 or node are shared by every
+        // interpreter the user runs
+        #[cfg(windows)]
         if let Some(path) = exe_path {
             let path = std::path::Path::new(&path);
             if let Some(file_name) = path.file_name() {
                 if let Some(name_str) = file_name.to_str() {
-                    #[cfg(windows)]
-                    {
-                        let output = Command::new("taskkill")
-                            .args(["/F", "/IM", name_str])
-                            .creation_flags(0x08000000)
-                            .output()
-                            .map_err(|e| e.to_string())?;
-                            
-                        if output.status.success() {
-                             let _ = app_handle.emit("app-output", serde_json::json!({
-                                "appId": &app_id,
-                                "line": format!("■ External process {} stopped", name_str)
-                            }));
-                            let _ = app_handle.emit("app-stopped", serde_json::json!({
-                                "appId": &app_id
-                            }));
-                            return Ok(());
-                        }
+                    let killed = process::native().kill_by_name(name_str)?;
+                    if killed > 0 {
+                        emit_system(&app_handle, &app_id, &format!("■ External process {} stopped", name_str));
+                        emit_stopped(&app_handle, &app_id, None);
+                        return Ok(());
                     }
                 }
             }
         }
+        #[cfg(not(windows))]
+        let _ = exe_path;
         Err("App is not running".to_string())
     }
 }
 
+/// The configured stop signal and timeout, or the defaults.
+fn stop_settings(
+    app_handle: &AppHandle,
+    app_id: &str,
+    stop_signal: Option<&str>,
+    stop_timeout: Option<u64>,
+) -> (process::StopSignal, Duration) {
+    let signal = match stop_signal.filter(|s| !s.trim().is_empty()) {
+        Some(name) => process::StopSignal::parse(name).unwrap_or_else(|| {
+            let fallback = process::StopSignal::default_for_os();
+            emit_system(app_handle, app_id, &format!("⚠ Unknown stop signal {}, using {}", name, fallback));
+            fallback
+        }),
+        None => process::StopSignal::default_for_os(),
+    };
+    (signal, Duration::from_secs(stop_timeout.unwrap_or(DEFAULT_STOP_TIMEOUT_SECS)))
+}
+
+/// Ask the app to exit, wait up to `timeout`, then kill its whole process tree.
+///
+/// A zero timeout skips the graceful request. Returns how the process
+/// ended, or `None` if it survived the forced kill.
+fn graceful_stop(
+    app_handle: &AppHandle,
+    app_id: &str,
+    pid: u32,
+    exit: &ExitWatch,
+    signal: process::StopSignal,
+    timeout: Duration,
+) -> Option<process::ExitInfo> {
+    let emit = |line: String| {
+        emit_system(app_handle, app_id, &line);
+    };
+    
+    if !timeout.is_zero() {
+        match process::native().request_stop(pid, signal) {
+            Ok(sent) => {
+                emit(format!("■ Sent {} to process {}, waiting up to {}s", sent, pid, timeout.as_secs()));
+                if let Some(info) = exit.wait_timeout(timeout) {
+                    emit(info.describe());
+                    return Some(info);
+                }
+                emit(format!("⚠ Still running after {}s, killing process tree", timeout.as_secs()));
+            }
+            Err(e) => emit(format!("⚠ Could not send {}: {}, killing process tree", signal, e)),
+        }
+    }
+    
+    // Once reaped the pid may already belong to someone else
+    if !exit.is_finished() {
+        if let Err(e) = process::native().kill_tree(pid) {
+            if !e.is_gone() {
+                emit(format!("⚠ Failed to kill process tree: {}", e));
+            }
+        }
+    }
+    let info = exit.wait_timeout(KILL_WAIT);
+    match &info {
+        Some(info) => emit(info.describe()),
+        None => emit(format!("⚠ Process {} is still running after being killed", pid)),
+    }
+    info
+}
+
+/// Whether any process has the executable's name. Only on Windows:
+/// elsewhere names like python3 or node are shared by unrelated programs.
 #[tauri::command]
 fn check_process_running(exe_path: String) -> bool {
-    let path = std::path::Path::new(&exe_path);
-    if let Some(file_name) = path.file_name() {
-        if let Some(name_str) = file_name.to_str() {
-            #[cfg(windows)]
-            {
-                // Use tasklist to check if process exists
-                // /FI "IMAGENAME eq name.exe" /NH (No Header)
-                let output = Command::new("tasklist")
-                    .args(["/FI", &format!("IMAGENAME eq {}", name_str), "/NH"])
-                    .creation_flags(0x08000000)
-                    .output();
-                    
-                if let Ok(out) = output {
-                    let stdout = String::from_utf8_lossy(&out.stdout);
-                    // If process found, it will list it. If not, it says "INFO: No tasks are running..."
-                    return stdout.to_lowercase().contains(&name_str.to_lowercase());
-                }
+    #[cfg(windows)]
+    {
+        let path = std::path::Path::new(&exe_path);
+        if let Some(file_name) = path.file_name() {
+            if let Some(name_str) = file_name.to_str() {
+                return process::native()
+                    .find_by_name(name_str)
+                    .map(|pids| !pids.is_empty())
+                    .unwrap_or(false);
             }
         }
     }
+    #[cfg(not(windows))]
+    let _ = exe_path;
     false
 }
 
 #[tauri::command]
 fn is_app_running(app_handle: AppHandle, app_id: String) -> bool {
-    let state = app_handle.state::<ProcessManager>();
-    let processes = state.processes.lock().unwrap();
-    processes.contains_key(&app_id)
+    app_handle.state::<ProcessManager>().is_running(&app_id)
+}
+
+/// Whether the tray should show the app as running: started by AppCtrl,
+/// or on Windows a process with the same executable name.
+fn tray_app_running<R: tauri::Runtime>(app: &tauri::AppHandle<R>, app_conf: &AppConfig) -> bool {
+    app.try_state::<ProcessManager>().is_some_and(|state| state.is_running(&app_conf.id))
+        || check_process_running(app_conf.executable_path.clone())
 }
 
 // Extract icon from EXE file and return as base64 data URL
@@ -446,294 +1554,524 @@ fn get_config_path() -> std::path::PathBuf {
     path
 }
 
+fn config_file() -> config::ConfigFile {
+    config::ConfigFile::new(get_config_path())
+}
+
+#[derive(Default)]
+struct ConfigState {
+    /// Set when config.json had to be recovered, until the frontend has
+    /// shown it.
+    recovery: Mutex<Option<config::Recovery>>,
+    /// The config as last loaded or saved by AppCtrl; changes made to the
+    /// file by other programs are reported against it.
+    current: Mutex<ConfigData>,
+}
+
+/// The saved config; an empty one if there is no file yet. A damaged file
+/// is replaced by its newest good backup.
 #[tauri::command]
-fn load_config() -> Result<String, String> {
-    let path = get_config_path();
-    if path.exists() {
-        std::fs::read_to_string(path).map_err(|e| e.to_string())
-    } else {
-        Ok("{}".to_string()) // Return empty JSON object if not found
+fn load_config(app_handle: AppHandle) -> Result<ConfigData, ConfigError> {
+    let state = app_handle.state::<ConfigState>();
+    let mut current = state.current.lock().unwrap();
+    let loaded = config_file().recover()?;
+    if let Some(recovery) = loaded.recovery {
+        *state.recovery.lock().unwrap() = Some(recovery);
     }
+    *current = loaded.config.clone();
+    Ok(loaded.config)
 }
 
+/// How config.json was last recovered, if the frontend hasn't been told.
 #[tauri::command]
-fn save_config(config: String) -> Result<(), String> {
+fn take_config_recovery(app_handle: AppHandle) -> Option<config::Recovery> {
+    app_handle.state::<ConfigState>().recovery.lock().unwrap().take()
+}
+
+/// Write the config, unless any field of it is invalid.
+#[tauri::command]
+fn save_config(app_handle: AppHandle, config: ConfigData) -> Result<(), ConfigError> {
+    let state = app_handle.state::<ConfigState>();
+    // Held while writing so the watcher doesn't take our own save for an
+    // outside change
+    let mut current = state.current.lock().unwrap();
+    config.validate(&current)?;
+    config_file().save(&config)?;
+    *current = config;
+    Ok(())
+}
+
+/// How often config.json is checked for changes made by other programs.
+const CONFIG_POLL: Duration = Duration::from_secs(1);
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct ConfigChangedEvent<'a> {
+    diff: config::ConfigDiff,
+    config: &'a ConfigData,
+}
+
+/// Reload config.json when an editor, a script or a sync client changes
+/// it. A change is picked up once the file has stayed the same for one
+/// poll, so a file that is still being written isn't read half-way.
+fn watch_config(app_handle: AppHandle) {
     let path = get_config_path();
-    std::fs::write(path, config).map_err(|e| e.to_string())
+    let stamp = |path: &std::path::Path| std::fs::metadata(path).ok().map(|meta| (meta.modified().ok(), meta.len()));
+    let mut seen = stamp(&path);
+    let mut handled = seen;
+    loop {
+        std::thread::sleep(CONFIG_POLL);
+        let now = stamp(&path);
+        if now != seen {
+            seen = now;
+            continue;
+        }
+        if now == handled {
+            continue;
+        }
+        handled = now;
+        // A deleted file is left alone; AppCtrl writes a new one on save
+        if now.is_some() {
+            reload_config(&app_handle);
+        }
+    }
+}
+
+/// Take over config.json as it is on disk and report what changed as
+/// `config-changed`. A file that doesn't parse or validate is reported as
+/// `config-invalid` and the config in use is kept.
+fn reload_config(app_handle: &AppHandle) {
+    let state = app_handle.state::<ConfigState>();
+    let mut current = state.current.lock().unwrap();
+    let checked = config_file().load().and_then(|loaded| match loaded.recovery {
+        Some(recovery) => Err(ConfigError::Io(format!("config.json could not be read: {}", recovery.error))),
+        None => loaded.config.validate(&current).map(|()| loaded.config),
+    });
+    let config = match checked {
+        Ok(config) => config,
+        Err(e) => {
+            let _ = app_handle.emit("config-invalid", e);
+            return;
+        }
+    };
+    let diff = current.diff(&config);
+    if diff.is_empty() {
+        return;
+    }
+    *current = config;
+    let _ = app_handle.emit("config-changed", ConfigChangedEvent { diff, config: &current });
+    drop(current);
+    refresh_tray_menu(app_handle);
 }
 
 #[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
 struct PortInfo {
     port: u16,
     pid: u32,
     name: String,
     protocol: String,
+    local_address: String,
+    family: String,
+    state: Option<String>,
 }
 
-#[tauri::command]
-async fn get_listening_ports() -> Result<Vec<PortInfo>, String> {
-    #[cfg(windows)]
-    {
-        use std::os::windows::process::CommandExt;
-        
-        // 1. Get all processes (PID -> Name)
-        let output = Command::new("tasklist")
-            .args(["/FO", "CSV", "/NH"])
-            .creation_flags(0x08000000)
-            .output()
-            .map_err(|e| format!("Failed to run tasklist: {}", e))?;
-            
-        let tasklist_out = String::from_utf8_lossy(&output.stdout);
-        let mut pid_map = HashMap::new();
-        
-        for line in tasklist_out.lines() {
-            // CSV format: "Name","PID",...
-            let parts: Vec<&str> = line.split("\",\"").collect();
-            if parts.len() >= 2 {
-                let name = parts[0].trim_matches('"').to_string();
-                let pid_str = parts[1].trim_matches('"');
-                if let Ok(pid) = pid_str.parse::<u32>() {
-                    pid_map.insert(pid, name);
-                }
-            }
-        }
-        
-        // 2. Get listening ports
-        let output = Command::new("netstat")
-            .args(["-ano"])
-            .creation_flags(0x08000000)
-            .output()
-            .map_err(|e| format!("Failed to run netstat: {}", e))?;
-            
-        let netstat_out = String::from_utf8_lossy(&output.stdout);
-        let mut ports = Vec::new();
-        
-        for line in netstat_out.lines() {
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            // Expected: Proto, Local Address, Foreign Address, State, PID
-            // TCP 0.0.0.0:80 0.0.0.0:0 LISTENING 1234
-            // UDP 0.0.0.0:123 *:* 1234
-            
-            if parts.len() >= 5 && parts[0] == "TCP" && parts[3] == "LISTENING" {
-                let local_addr = parts[1];
-                let pid_str = parts[4];
-                
-                if let Some(port_str) = local_addr.split(':').last() {
-                    if let (Ok(port), Ok(pid)) = (port_str.parse::<u16>(), pid_str.parse::<u32>()) {
-                        let name = pid_map.get(&pid).cloned().unwrap_or_else(|| "Unknown".to_string());
-                        ports.push(PortInfo {
-                            port,
-                            pid,
-                            name,
-                            protocol: "TCP".to_string(),
-                        });
-                    }
-                }
-            } else if parts.len() >= 4 && parts[0] == "UDP" {
-                // UDP doesn't have "State" column usually, PID is at index 3
-                let local_addr = parts[1];
-                let pid_str = parts[3];
-                 if let Some(port_str) = local_addr.split(':').last() {
-                    if let (Ok(port), Ok(pid)) = (port_str.parse::<u16>(), pid_str.parse::<u32>()) {
-                        let name = pid_map.get(&pid).cloned().unwrap_or_else(|| "Unknown".to_string());
-                        ports.push(PortInfo {
-                            port,
-                            pid,
-                            name,
-                            protocol: "UDP".to_string(),
-                        });
-                    }
-                }
-            }
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ConnectionInfo {
+    protocol: String,
+    family: String,
+    local_address: String,
+    local_port: u16,
+    remote_address: Option<String>,
+    remote_port: Option<u16>,
+    state: Option<String>,
+    pid: u32,
+    name: String,
+}
+
+// One socket from the OS socket table, shared by the ports and
+// connections views
+struct SocketRow {
+    protocol: &'static str,
+    local: std::net::SocketAddr,
+    remote: Option<std::net::SocketAddr>,
+    state: String,
+    listening: bool,
+    pid: u32,
+    name: String,
+}
+
+fn address_family(addr: &std::net::SocketAddr) -> String {
+    if addr.is_ipv4() { "IPv4" } else { "IPv6" }.to_string()
+}
+
+impl From<SocketRow> for PortInfo {
+    fn from(row: SocketRow) -> Self {
+        Self {
+            port: row.local.port(),
+            pid: row.pid,
+            name: row.name,
+            protocol: row.protocol.to_string(),
+            local_address: row.local.ip().to_string(),
+            family: address_family(&row.local),
+            state: (row.protocol == "TCP").then_some(row.state),
         }
-        
-        // Sort by port
-        ports.sort_by_key(|p| p.port);
-        // Deduplicate (sometimes netstat shows multiple lines for same socket)
-        ports.dedup_by(|a, b| a.port == b.port && a.pid == b.pid && a.protocol == b.protocol);
-        
-        Ok(ports)
     }
-    #[cfg(not(windows))]
-    {
-        Err("Not supported on non-Windows yet".to_string())
+}
+
+impl From<SocketRow> for ConnectionInfo {
+    fn from(row: SocketRow) -> Self {
+        Self {
+            protocol: row.protocol.to_string(),
+            family: address_family(&row.local),
+            local_address: row.local.ip().to_string(),
+            local_port: row.local.port(),
+            remote_address: row.remote.map(|r| r.ip().to_string()),
+            remote_port: row.remote.map(|r| r.port()),
+            state: (row.protocol == "TCP").then_some(row.state),
+            pid: row.pid,
+            name: row.name,
+        }
     }
 }
 
+#[cfg(windows)]
+fn list_sockets() -> Result<Vec<SocketRow>, String> {
+    // 1. Get all processes (PID -> Name)
+    let pid_map: HashMap<u32, String> = process::native()
+        .list()?
+        .into_iter()
+        .map(|p| (p.pid, p.name))
+        .collect();
+    
+    // 2. Get sockets
+    let output = Command::new("netstat")
+        .args(["-ano"])
+        .creation_flags(0x08000000)
+        .output()
+        .map_err(|e| format!("Failed to run netstat: {}", e))?;
+        
+    let netstat_out = String::from_utf8_lossy(&output.stdout);
+    
+    Ok(netstat_out
+        .lines()
+        .filter_map(netstat::parse_netstat_line)
+        .map(|row| SocketRow {
+            protocol: row.protocol,
+            local: row.local,
+            remote: row.remote,
+            listening: row.is_listening(),
+            name: pid_map.get(&row.pid).cloned().unwrap_or_else(|| "Unknown".to_string()),
+            state: row.state,
+            pid: row.pid,
+        })
+        .collect())
+}
+
+#[cfg(target_os = "linux")]
+fn list_sockets() -> Result<Vec<SocketRow>, String> {
+    let root = std::path::Path::new("/proc");
+    let sockets = procnet::read_sockets(root)
+        .map_err(|e| format!("Failed to read /proc/net: {}", e))?;
+    // Sockets of processes we can't inspect keep pid 0 / "Unknown"
+    let owners = procnet::socket_owners(root);
+    let mut names: HashMap<u32, String> = HashMap::new();
+    
+    Ok(sockets
+        .into_iter()
+        .map(|socket| {
+            let pid = owners.get(&socket.inode).copied().unwrap_or(0);
+            let name = names
+                .entry(pid)
+                .or_insert_with(|| procnet::process_name(root, pid).unwrap_or_else(|| "Unknown".to_string()))
+                .clone();
+            SocketRow {
+                protocol: socket.protocol,
+                local: socket.local,
+                // Unconnected UDP sockets have no peer
+                remote: (socket.protocol == "TCP" || socket.remote.port() != 0).then_some(socket.remote),
+                state: socket.state.to_string(),
+                listening: socket.is_listening(),
+                pid,
+                name,
+            }
+        })
+        .collect())
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+fn list_sockets() -> Result<Vec<SocketRow>, String> {
+    Err("Not supported on this OS yet".to_string())
+}
 
 #[tauri::command]
-async fn kill_process_by_pid(pid: u32) -> Result<(), String> {
-    #[cfg(windows)]
-    {
-        use std::os::windows::process::CommandExt;
-        let output = Command::new("taskkill")
-            .args(["/F", "/PID", &pid.to_string()])
-            .creation_flags(0x08000000)
-            .output()
-            .map_err(|e| e.to_string())?;
-            
-        if output.status.success() {
-            Ok(())
-        } else {
-            Err(String::from_utf8_lossy(&output.stderr).to_string())
-        }
-    }
-    #[cfg(not(windows))]
-    {
-        Err("Not supported on non-Windows yet".to_string())
-    }
+async fn get_listening_ports() -> Result<Vec<PortInfo>, String> {
+    let mut ports: Vec<PortInfo> = list_sockets()?
+        .into_iter()
+        .filter(|s| s.listening)
+        .map(PortInfo::from)
+        .collect();
+    
+    // Sort by port, then bind address
+    ports.sort_by(|a, b| (a.port, &a.protocol, &a.local_address).cmp(&(b.port, &b.protocol, &b.local_address)));
+    // Deduplicate (sometimes netstat shows multiple lines for same socket)
+    ports.dedup_by(|a, b| {
+        a.port == b.port && a.pid == b.pid && a.protocol == b.protocol && a.local_address == b.local_address
+    });
+    
+    Ok(ports)
 }
 
 #[tauri::command]
-async fn kill_process_by_name(name: String) -> Result<(), String> {
-    #[cfg(windows)]
-    {
-        use std::os::windows::process::CommandExt;
-        let output = Command::new("taskkill")
-            .args(["/F", "/IM", &name])
-            .creation_flags(0x08000000)
-            .output()
-            .map_err(|e| e.to_string())?;
-            
-        if output.status.success() {
-            Ok(())
-        } else {
-            // Check if error is "The process ... not found" (which means success effectively)
-            let err = String::from_utf8_lossy(&output.stderr).to_string();
-            if err.contains("not found") {
-                Ok(())
-            } else {
-                Err(err)
-            }
-        }
-    }
-    #[cfg(not(windows))]
-    {
-        Err("Not supported on non-Windows yet".to_string())
-    }
+async fn get_connections() -> Result<Vec<ConnectionInfo>, String> {
+    let mut connections: Vec<ConnectionInfo> = list_sockets()?
+        .into_iter()
+        .filter(|s| !s.listening)
+        .map(ConnectionInfo::from)
+        .collect();
+    
+    // Group by owning process, then by remote endpoint
+    connections.sort_by(|a, b| {
+        (a.name.to_lowercase(), a.pid, &a.remote_address, a.remote_port, a.local_port)
+            .cmp(&(b.name.to_lowercase(), b.pid, &b.remote_address, b.remote_port, b.local_port))
+    });
+    
+    Ok(connections)
+}
+
+
+#[tauri::command]
+async fn kill_process_by_pid(pid: u32) -> Result<(), process::ProcessError> {
+    process::native().kill(pid)
+}
+
+#[tauri::command]
+async fn kill_process_by_name(name: String) -> Result<(), process::ProcessError> {
+    // Nothing left to kill counts as success
+    process::native().kill_by_name(&name).map(|_| ())
 }
 
 #[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
 struct ProcessInfo {
     pid: u32,
     name: String,
     memory: String,
+    command_line: Option<String>,
+    user: Option<String>,
+    #[serde(skip)]
+    kernel_thread: bool,
+}
+
+// Default names hidden from the Task Killer, overridable per OS with
+// `systemProcesses` in config.json (keyed by "windows", "linux", ...)
+const WINDOWS_SYSTEM_PROCESSES: &[&str] = &[
+    "System Idle Process", "System", "Registry", "smss.exe", "csrss.exe", 
+    "wininit.exe", "services.exe", "lsass.exe", "svchost.exe", "fontdrvhost.exe", 
+    "dwm.exe", "winlogon.exe", "spoolsv.exe", "Memory Compression", "taskhostw.exe",
+    "RuntimeBroker.exe", "SearchUI.exe", "ShellExperienceHost.exe", "ApplicationFrameHost.exe",
+    "ctfmon.exe", "conhost.exe", "dllhost.exe", "sihost.exe", "SearchApp.exe",
+    "StartMenuExperienceHost.exe", "TextInputHost.exe", "SecurityHealthService.exe",
+    "NisSrv.exe", "MsMpEng.exe", "audiodg.exe"
+];
+
+const LINUX_SYSTEM_PROCESSES: &[&str] = &[
+    "systemd", "systemd-journald", "systemd-udevd", "systemd-logind", "systemd-resolved",
+    "systemd-timesyncd", "systemd-networkd", "systemd-oomd", "dbus-daemon", "dbus-broker",
+    "polkitd", "udisksd", "upowerd", "rtkit-daemon", "accounts-daemon", "NetworkManager",
+    "wpa_supplicant", "ModemManager", "agetty", "cron", "crond", "rsyslogd", "irqbalance",
+    "thermald", "avahi-daemon", "snapd", "gdm", "gdm-session-worker", "sddm", "xdg-permission-store"
+];
+
+fn system_process_filter(app_handle: &AppHandle) -> Vec<String> {
+    if let Some(list) = saved_config(app_handle).system_processes.and_then(|mut m| m.remove(std::env::consts::OS)) {
+        return list;
+    }
+    let defaults: &[&str] = if cfg!(windows) {
+        WINDOWS_SYSTEM_PROCESSES
+    } else if cfg!(target_os = "linux") {
+        LINUX_SYSTEM_PROCESSES
+    } else {
+        &[]
+    };
+    defaults.iter().map(|s| s.to_string()).collect()
+}
+
+// Same layout as tasklist's "Mem Usage" column, e.g. "12,345 K"
+fn format_memory_kb(kb: u64) -> String {
+    let digits = kb.to_string();
+    let mut out = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    format!("{} K", out)
+}
+
+fn list_processes() -> Result<Vec<ProcessInfo>, String> {
+    Ok(process::native()
+        .list()?
+        .into_iter()
+        .map(|p| ProcessInfo {
+            pid: p.pid,
+            name: p.name,
+            memory: p.memory_kb.map(format_memory_kb).unwrap_or_default(),
+            command_line: p.command_line,
+            user: p.user,
+            kernel_thread: p.kernel_thread,
+        })
+        .collect())
 }
 
 #[tauri::command]
-async fn get_processes() -> Result<Vec<ProcessInfo>, String> {
-    #[cfg(windows)]
-    {
-        use std::os::windows::process::CommandExt;
-        let output = Command::new("tasklist")
-            .args(["/FO", "CSV", "/NH"])
-            .creation_flags(0x08000000)
-            .output()
-            .map_err(|e| format!("Failed to run tasklist: {}", e))?;
-
-        let tasklist_out = String::from_utf8_lossy(&output.stdout);
-        let mut processes = Vec::new();
-        
-        let system_processes = [
-            "System Idle Process", "System", "Registry", "smss.exe", "csrss.exe", 
-            "wininit.exe", "services.exe", "lsass.exe", "svchost.exe", "fontdrvhost.exe", 
-            "dwm.exe", "winlogon.exe", "spoolsv.exe", "Memory Compression", "taskhostw.exe",
-            "RuntimeBroker.exe", "SearchUI.exe", "ShellExperienceHost.exe", "ApplicationFrameHost.exe",
-            "ctfmon.exe", "conhost.exe", "dllhost.exe", "sihost.exe", "SearchApp.exe",
-            "StartMenuExperienceHost.exe", "TextInputHost.exe", "SecurityHealthService.exe",
-            "NisSrv.exe", "MsMpEng.exe", "audiodg.exe"
-        ];
-
-        for line in tasklist_out.lines() {
-            // "Name","PID","Session Name","Session#","Mem Usage"
-            let parts: Vec<&str> = line.split("\",\"").collect();
-            if parts.len() >= 5 {
-                let name = parts[0].trim_matches('"').to_string();
-                
-                // Filter system processes
-                if system_processes.iter().any(|&s| s.eq_ignore_ascii_case(&name)) {
-                    continue;
-                }
+async fn get_processes(app_handle: AppHandle, show_system: Option<bool>) -> Result<Vec<ProcessInfo>, String> {
+    let mut processes = list_processes()?;
+    
+    // Filter system processes
+    if !show_system.unwrap_or(false) {
+        let system_processes = system_process_filter(&app_handle);
+        processes.retain(|p| {
+            !p.kernel_thread && !system_processes.iter().any(|s| s.eq_ignore_ascii_case(&p.name))
+        });
+    }
+    
+    // Sort by name
+    processes.sort_by_key(|p| p.name.to_lowercase());
+    
+    Ok(processes)
+}
 
-                let pid_str = parts[1].trim_matches('"');
-                let mem_str = parts[4].trim_matches('"'); // e.g. "12,345 K"
-                
-                if let Ok(pid) = pid_str.parse::<u32>() {
-                    processes.push(ProcessInfo {
-                        pid,
-                        name,
-                        memory: mem_str.to_string(),
-                    });
-                }
-            }
+impl AppConfig {
+    fn restart_options(&self) -> restart::RestartOptions {
+        restart::RestartOptions {
+            policy: self.restart_policy.clone(),
+            max_restarts: self.max_restarts,
         }
-        
-        // Sort by name
-        processes.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
-        
-        Ok(processes)
     }
-    #[cfg(not(windows))]
-    {
-        Err("Not supported on non-Windows yet".to_string())
+    
+    fn launch_spec(&self) -> LaunchSpec {
+        LaunchSpec {
+            path: self.executable_path.clone(),
+            app_type: self.app_type.clone(),
+            working_dir: self.working_directory.clone().unwrap_or_default(),
+            args: self.arguments.clone().unwrap_or_default(),
+            env_vars: self.environment_vars.clone().unwrap_or_default(),
+            shell: self.shell.clone(),
+            encoding: self.encoding.clone(),
+            force_color: self.force_color.unwrap_or(false),
+            terminal: self.terminal.unwrap_or(false),
+            keep_stdin: self.keep_stdin.unwrap_or(false),
+            triggers: self.triggers.clone().unwrap_or_default(),
+            health: self.health.clone().unwrap_or_default(),
+        }
+    }
+    
+    /// Start the app and its dependencies with their saved settings, as
+    /// the tray does.
+    async fn start(&self, app_handle: AppHandle) -> Result<(), String> {
+        start_dependencies(&app_handle, &self.id).await?;
+        if !launch_app(&app_handle, &self.id, &self.restart_options(), &self.launch_spec())? {
+            return Err("App is already running".to_string());
+        }
+        Ok(())
     }
 }
 
-#[derive(serde::Deserialize, serde::Serialize, Clone)]
-struct AppConfig {
-    id: String,
-    name: String,
-    #[serde(rename = "executablePath")]
-    executable_path: String,
-    #[serde(rename = "appType")]
-    app_type: String,
-    #[serde(rename = "workingDirectory")]
-    working_directory: Option<String>,
-    #[serde(rename = "arguments")]
-    arguments: Option<String>,
-    #[serde(rename = "environmentVars")]
-    environment_vars: Option<String>,
-    #[serde(rename = "icon")]
-    icon: Option<String>,
-    #[serde(rename = "isRunning")]
-    is_running: Option<bool>,
+/// The config in use: as last loaded or saved by AppCtrl, or taken over
+/// from an outside edit that passed validation.
+fn saved_config<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>) -> ConfigData {
+    app_handle.state::<ConfigState>().current.lock().unwrap().clone()
+}
+
+fn saved_apps(app_handle: &AppHandle) -> Vec<AppConfig> {
+    saved_config(app_handle).apps
+}
+
+fn find_group(config: &ConfigData, group_id: &str) -> Result<groups::GroupConfig, String> {
+    config
+        .groups
+        .iter()
+        .find(|group| group.id == group_id)
+        .cloned()
+        .ok_or_else(|| "Group not found".to_string())
 }
 
-#[derive(serde::Deserialize, serde::Serialize, Default)]
-struct ConfigData {
-    apps: Option<Vec<AppConfig>>,
+/// The saved settings of one app.
+fn find_app_config(app_handle: &AppHandle, app_id: &str) -> Option<AppConfig> {
+    saved_apps(app_handle).into_iter().find(|app| app.id == app_id)
 }
 
 fn build_tray_menu<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> tauri::Result<Menu<R>> {
     let show = MenuItem::with_id(app, "show", "Show AppCtrl", true, None::<&str>)?;
     let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
     
-    let config_json = load_config().unwrap_or_else(|_| "{}".to_string());
-    let config: ConfigData = serde_json::from_str(&config_json).unwrap_or_default();
-    
-    let mut submenu_items = Vec::new();
+    let ConfigData { apps, groups, .. } = saved_config(app);
+    let statuses = match app.try_state::<ProcessManager>() {
+        Some(state) => group_statuses(&state, &groups),
+        None => Vec::new(),
+    };
     
-    if let Some(apps) = config.apps {
-        for app_conf in apps {
-            let is_running = check_process_running(app_conf.executable_path.clone());
-            let icon = if is_running { "🟢" } else { "🔴" };
-            let title = format!("{} {}", icon, app_conf.name);
-            let id = format!("toggle_app:{}", app_conf.id);
-            
-            if let Ok(item) = MenuItem::with_id(app, &id, &title, true, None::<&str>) {
-                submenu_items.push(item);
-            }
+    // One submenu per group, the apps in no group under "Open"
+    let mut group_submenus = Vec::new();
+    for (group, status) in groups.iter().zip(&statuses) {
+        let start = MenuItem::with_id(app, format!("start_group:{}", group.id), "▶ Start all", true, None::<&str>)?;
+        let stop = MenuItem::with_id(app, format!("stop_group:{}", group.id), "■ Stop all", true, None::<&str>)?;
+        let separator = PredefinedMenuItem::separator(app)?;
+        let members: Vec<_> = apps
+            .iter()
+            .filter(|app_conf| group.apps.contains(&app_conf.id))
+            .filter_map(|app_conf| app_menu_item(app, app_conf).ok())
+            .collect();
+        
+        let mut item_refs: Vec<&dyn IsMenuItem<R>> = vec![&start, &stop, &separator];
+        for item in &members {
+            item_refs.push(item);
         }
+        let title = format!("{} {}", status.state.icon(), group.name);
+        group_submenus.push(Submenu::with_items(app, title, true, &item_refs)?);
     }
     
-    let mut item_refs: Vec<&dyn tauri::menu::IsMenuItem<R>> = Vec::new();
+    let submenu_items: Vec<_> = apps
+        .iter()
+        .filter(|app_conf| !groups.iter().any(|group| group.apps.contains(&app_conf.id)))
+        .filter_map(|app_conf| app_menu_item(app, app_conf).ok())
+        .collect();
+    
+    let mut item_refs: Vec<&dyn IsMenuItem<R>> = Vec::new();
     for item in &submenu_items {
         item_refs.push(item);
     }
     
     let open_submenu = Submenu::with_items(app, "Open", true, &item_refs)?;
     
-    Menu::with_items(app, &[&show, &open_submenu, &quit])
+    let mut menu_refs: Vec<&dyn IsMenuItem<R>> = vec![&show];
+    for submenu in &group_submenus {
+        menu_refs.push(submenu);
+    }
+    menu_refs.push(&open_submenu);
+    menu_refs.push(&quit);
+    Menu::with_items(app, &menu_refs)
+}
+
+/// The item that starts or stops one app.
+fn app_menu_item<R: tauri::Runtime>(app: &tauri::AppHandle<R>, app_conf: &AppConfig) -> tauri::Result<MenuItem<R>> {
+    let is_running = tray_app_running(app, app_conf);
+    let icon = if is_running { "🟢" } else { "🔴" };
+    let title = format!("{} {}", icon, app_conf.name);
+    let id = format!("toggle_app:{}", app_conf.id);
+    MenuItem::with_id(app, id, title, true, None::<&str>)
+}
+
+/// Rebuild the tray menu so its status dots are current.
+fn refresh_tray_menu(app_handle: &AppHandle) {
+    if let Ok(new_menu) = build_tray_menu(app_handle) {
+        if let Some(tray) = app_handle.tray_by_id("main") {
+            let _ = tray.set_menu(Some(new_menu));
+        }
+    }
 }
 
 #[derive(serde::Serialize)]
@@ -1091,6 +2429,7 @@ struct LockProcessInfo {
     name: String,
 }
 
+#[cfg(windows)]
 #[repr(C)]
 #[derive(Clone, Copy)]
 #[allow(non_camel_case_types)]
@@ -1099,6 +2438,7 @@ struct RM_UNIQUE_PROCESS {
     ProcessStartTime: winapi::shared::minwindef::FILETIME,
 }
 
+#[cfg(windows)]
 #[repr(C)]
 #[derive(Clone, Copy)]
 #[allow(non_camel_case_types)]
@@ -1271,6 +2611,7 @@ fn get_file_lock_processes(path: String) -> Result<Vec<LockProcessInfo>, String>
     }
     #[cfg(not(windows))]
     {
+        let _ = path;
         Err("Chỉ hỗ trợ trên Windows".to_string())
     }
 }
@@ -1279,15 +2620,9 @@ fn get_file_lock_processes(path: String) -> Result<Vec<LockProcessInfo>, String>
 async fn force_delete_file(path: String) -> Result<(), String> {
     #[cfg(windows)]
     {
-        use std::process::Command;
-        use std::os::windows::process::CommandExt;
-        
         if let Ok(locks) = get_lock_processes(&path) {
             for lock in locks {
-                let _ = Command::new("taskkill")
-                    .args(["/F", "/PID", &lock.pid.to_string()])
-                    .creation_flags(0x08000000)
-                    .output();
+                let _ = process::native().kill(lock.pid);
             }
         }
         
@@ -1305,6 +2640,7 @@ async fn force_delete_file(path: String) -> Result<(), String> {
     }
     #[cfg(not(windows))]
     {
+        let _ = path;
         Err("Chỉ hỗ trợ trên Windows".to_string())
     }
 }
@@ -1336,7 +2672,13 @@ pub fn run() {
         }))
         .manage(ProcessManager::new())
         .manage(AppSettings { minimize_to_tray: Mutex::new(false) })
+        .manage(ConfigState::default())
         .setup(|app| {
+            // Repair a damaged config.json before anything reads it; errors
+            // reach the frontend when it loads the config itself
+            let _ = load_config(app.handle().clone());
+            let app_handle = app.handle().clone();
+            std::thread::spawn(move || watch_config(app_handle));
             let menu = build_tray_menu(app.handle())?;
             
             let _tray = TrayIconBuilder::with_id("main")
@@ -1355,38 +2697,41 @@ pub fn run() {
                          
                          tauri::async_runtime::spawn(async move {
                              // Load config to get app details
-                             let config_json = load_config().unwrap_or_else(|_| "{}".to_string());
-                             let config: ConfigData = serde_json::from_str(&config_json).unwrap_or_default();
-                             
-                             if let Some(apps) = config.apps {
-                                 if let Some(app_conf) = apps.iter().find(|a| a.id == app_id) {
-                                     let is_running = check_process_running(app_conf.executable_path.clone());
-                                     
-                                     if is_running {
-                                         // Stop
-                                         let _ = stop_app(app_handle.clone(), app_id.clone(), Some(app_conf.executable_path.clone())).await;
-                                     } else {
-                                         // Start
-                                         let _ = start_app(
-                                             app_handle.clone(),
-                                             app_id.clone(),
-                                             app_conf.executable_path.clone(),
-                                             app_conf.app_type.clone(),
-                                             app_conf.working_directory.clone().unwrap_or_default(),
-                                             app_conf.arguments.clone().unwrap_or_default(),
-                                             app_conf.environment_vars.clone().unwrap_or_default()
-                                         ).await;
-                                     }
-                                     
-                                     // Rebuild and update menu
-                                     if let Ok(new_menu) = build_tray_menu(&app_handle) {
-                                         if let Some(tray) = app_handle.tray_by_id("main") {
-                                             let _ = tray.set_menu(Some(new_menu));
-                                         }
-                                     }
+                             if let Some(app_conf) = find_app_config(&app_handle, &app_id) {
+                                 let is_running = tray_app_running(&app_handle, &app_conf);
+                                 
+                                 if is_running {
+                                     // Stop
+                                     let _ = stop_app(
+                                         app_handle.clone(),
+                                         app_id.clone(),
+                                         Some(app_conf.executable_path.clone()),
+                                         app_conf.stop_signal.clone(),
+                                         app_conf.stop_timeout
+                                     ).await;
+                                 } else {
+                                     // Start
+                                     let _ = app_conf.start(app_handle.clone()).await;
                                  }
+                                 
+                                 // Rebuild and update menu
+                                 refresh_tray_menu(&app_handle);
                              }
                          });
+                    } else if let Some(group_id) = id.strip_prefix("start_group:") {
+                         let group_id = group_id.to_string();
+                         let app_handle = app.clone();
+                         tauri::async_runtime::spawn(async move {
+                             let _ = start_group(app_handle.clone(), group_id).await;
+                             refresh_tray_menu(&app_handle);
+                         });
+                    } else if let Some(group_id) = id.strip_prefix("stop_group:") {
+                         let group_id = group_id.to_string();
+                         let app_handle = app.clone();
+                         tauri::async_runtime::spawn(async move {
+                             let _ = stop_group(app_handle.clone(), group_id).await;
+                             refresh_tray_menu(&app_handle);
+                         });
                     }
                 })
                 .on_tray_icon_event(|tray: &tauri::tray::TrayIcon, event| {
@@ -1419,13 +2764,27 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             start_app,
             stop_app,
+            start_group,
+            stop_group,
+            get_group_statuses,
             is_app_running,
+            get_app_logs,
+            write_app_stdin,
+            send_app_input,
+            get_app_error_counts,
+            get_app_health,
+            reset_app_error_count,
+            resize_app_terminal,
+            list_app_log_runs,
+            read_app_log,
             extract_exe_icon,
             check_process_running,
             set_minimize_to_tray,
             load_config,
+            take_config_recovery,
             save_config,
             get_listening_ports,
+            get_connections,
             kill_process_by_pid,
             kill_process_by_name,
             get_processes,
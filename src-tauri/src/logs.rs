@@ -0,0 +1,548 @@
+// Persistent per-app output logs.
+//
+// Layout: `logs/<app id>/<run id>-<segment>.log` next to config.json. A
+// run is one spawn of the app and starts at segment 000; a new segment is
+// opened when the current one gets too big or too old. Every line is
+// "<timestamp>\t<stream>\t<text>".
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LogSettings {
+    /// Start a new segment once the current one reaches this size.
+    pub max_file_size_kb: u64,
+    /// ...or once it has been open this long; 0 disables age rotation.
+    pub max_file_age_hours: u64,
+    /// Runs last written longer ago than this are deleted; 0 keeps them.
+    pub retention_days: u64,
+    /// Runs kept per app, oldest deleted first; 0 means no limit.
+    pub max_runs: usize,
+    /// Keys this version doesn't know, kept as they are.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Default for LogSettings {
+    fn default() -> Self {
+        Self {
+            max_file_size_kb: 10 * 1024,
+            max_file_age_hours: 24,
+            retention_days: 14,
+            max_runs: 20,
+            extra: Default::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogStream {
+    Stdout,
+    Stderr,
+    /// Lines AppCtrl writes itself ("✓ Started", exit reports, ...)
+    System,
+}
+
+impl LogStream {
+    fn as_str(&self) -> &'static str {
+        match self {
+            LogStream::Stdout => "stdout",
+            LogStream::Stderr => "stderr",
+            LogStream::System => "system",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "stdout" => Some(LogStream::Stdout),
+            "stderr" => Some(LogStream::Stderr),
+            "system" => Some(LogStream::System),
+            _ => None,
+        }
+    }
+}
+
+/// Recent output of one app, numbered so a late subscriber can backfill
+/// and then continue with live events without gaps or duplicates.
+pub struct LogBuffer {
+    capacity: usize,
+    next_seq: u64,
+    lines: VecDeque<LogRecord>,
+}
+
+/// One line of output as sent in `app-output` events.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogRecord {
+    /// Starts at 1 and keeps counting across runs of the app.
+    pub seq: u64,
+    pub timestamp: String,
+    pub stream: LogStream,
+    /// Run the line belongs to; `None` before the app was first started.
+    pub run_id: Option<String>,
+    /// The line without escape sequences.
+    pub text: String,
+    /// Colors and attributes of `text`; left out for unstyled lines.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub spans: Vec<crate::ansi::Span>,
+    /// An unfinished line, superseded by the next record of its stream.
+    pub partial: bool,
+}
+
+impl LogBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            next_seq: 1,
+            lines: VecDeque::new(),
+        }
+    }
+
+    /// Store a line under the next seq, dropping the oldest one when full.
+    pub fn push(
+        &mut self,
+        now: SystemTime,
+        stream: LogStream,
+        run_id: Option<&str>,
+        line: crate::ansi::StyledText,
+        partial: bool,
+    ) -> &LogRecord {
+        // A superseded partial line is of no use to a later backfill
+        if let Some(pos) = self.lines.iter().rposition(|l| l.stream == stream) {
+            if self.lines[pos].partial {
+                self.lines.remove(pos);
+            }
+        }
+        if self.lines.len() == self.capacity {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(LogRecord {
+            seq: self.next_seq,
+            timestamp: format_timestamp(now),
+            stream,
+            run_id: run_id.map(str::to_string),
+            text: line.text,
+            spans: line.spans,
+            partial,
+        });
+        self.next_seq += 1;
+        self.lines.back().unwrap()
+    }
+
+    /// Up to `limit` lines with a seq above `since_seq`, oldest first.
+    pub fn since(&self, since_seq: u64, limit: usize) -> Vec<LogRecord> {
+        // Seqs only ever grow, though dropped partial lines leave gaps
+        let skip = self.lines.partition_point(|l| l.seq <= since_seq);
+        self.lines.iter().skip(skip).take(limit).cloned().collect()
+    }
+}
+
+/// Appends one run's output to its segment files.
+pub struct LogWriter {
+    dir: PathBuf,
+    run_id: String,
+    settings: LogSettings,
+    segment: u32,
+    file: File,
+    size: u64,
+    opened: SystemTime,
+}
+
+impl LogWriter {
+    /// Start a new run in `dir`, pruning old runs per the retention settings.
+    pub fn create(dir: &Path, run_id: &str, settings: LogSettings) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        prune(dir, &settings, SystemTime::now())?;
+        Ok(Self {
+            file: open_segment(dir, run_id, 0)?,
+            dir: dir.to_path_buf(),
+            run_id: run_id.to_string(),
+            settings,
+            segment: 0,
+            size: 0,
+            opened: SystemTime::now(),
+        })
+    }
+
+    pub fn write(&mut self, now: SystemTime, stream: LogStream, text: &str) -> io::Result<()> {
+        if self.should_rotate(now) {
+            self.segment += 1;
+            self.file = open_segment(&self.dir, &self.run_id, self.segment)?;
+            self.size = 0;
+            self.opened = now;
+        }
+        // One record per line, whatever the app printed
+        let text = text.replace(['\r', '\n'], " ");
+        let line = format!("{}\t{}\t{}\n", format_timestamp(now), stream.as_str(), text);
+        self.file.write_all(line.as_bytes())?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+
+    fn should_rotate(&self, now: SystemTime) -> bool {
+        if self.size == 0 {
+            return false;
+        }
+        let too_big = self.size >= self.settings.max_file_size_kb.saturating_mul(1024);
+        let max_age = Duration::from_secs(self.settings.max_file_age_hours.saturating_mul(3600));
+        let too_old = !max_age.is_zero() && now.duration_since(self.opened).unwrap_or_default() >= max_age;
+        too_big || too_old
+    }
+}
+
+fn segment_name(run_id: &str, segment: u32) -> String {
+    format!("{}-{:03}.log", run_id, segment)
+}
+
+fn open_segment(dir: &Path, run_id: &str, segment: u32) -> io::Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join(segment_name(run_id, segment)))
+}
+
+/// `<run id>-<segment>.log` -> (run id, segment)
+fn parse_segment_name(name: &str) -> Option<(&str, u32)> {
+    let stem = name.strip_suffix(".log")?;
+    let (run_id, segment) = stem.rsplit_once('-')?;
+    Some((run_id, segment.parse().ok()?))
+}
+
+struct RunFiles {
+    segments: Vec<(u32, PathBuf)>,
+    size: u64,
+    modified: SystemTime,
+}
+
+/// Runs in `dir` keyed by run id, which sorts chronologically.
+fn scan_runs(dir: &Path) -> io::Result<BTreeMap<String, RunFiles>> {
+    let mut runs: BTreeMap<String, RunFiles> = BTreeMap::new();
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(runs),
+        Err(e) => return Err(e),
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let Some((run_id, segment)) = name.to_str().and_then(parse_segment_name) else {
+            continue;
+        };
+        let Ok(meta) = entry.metadata() else { continue };
+        let modified = meta.modified().unwrap_or(UNIX_EPOCH);
+        let run = runs.entry(run_id.to_string()).or_insert_with(|| RunFiles {
+            segments: Vec::new(),
+            size: 0,
+            modified,
+        });
+        run.segments.push((segment, entry.path()));
+        run.size += meta.len();
+        run.modified = run.modified.max(modified);
+    }
+    for run in runs.values_mut() {
+        run.segments.sort();
+    }
+    Ok(runs)
+}
+
+/// Delete runs past the retention age, then the oldest ones beyond
+/// `max_runs` (leaving room for the run about to start).
+pub fn prune(dir: &Path, settings: &LogSettings, now: SystemTime) -> io::Result<()> {
+    let runs = scan_runs(dir)?;
+    let max_age = Duration::from_secs(settings.retention_days.saturating_mul(86400));
+    let mut kept = Vec::new();
+    for run in runs.into_values() {
+        let expired = !max_age.is_zero() && now.duration_since(run.modified).unwrap_or_default() > max_age;
+        if expired {
+            remove_run(&run);
+        } else {
+            kept.push(run);
+        }
+    }
+    if settings.max_runs > 0 && kept.len() >= settings.max_runs {
+        let excess = kept.len() + 1 - settings.max_runs;
+        for run in &kept[..excess] {
+            remove_run(run);
+        }
+    }
+    Ok(())
+}
+
+fn remove_run(run: &RunFiles) {
+    for (_, path) in &run.segments {
+        let _ = fs::remove_file(path);
+    }
+}
+
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunSummary {
+    pub run_id: String,
+    pub segments: usize,
+    pub size_bytes: u64,
+    /// Last write, RFC 3339 UTC.
+    pub last_write: String,
+}
+
+/// All logged runs of an app, newest first.
+pub fn list_runs(dir: &Path) -> io::Result<Vec<RunSummary>> {
+    Ok(scan_runs(dir)?
+        .into_iter()
+        .rev()
+        .map(|(run_id, run)| RunSummary {
+            run_id,
+            segments: run.segments.len(),
+            size_bytes: run.size,
+            last_write: format_timestamp(run.modified),
+        })
+        .collect())
+}
+
+#[derive(Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogLine {
+    pub timestamp: String,
+    pub stream: LogStream,
+    pub text: String,
+}
+
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogPage {
+    pub run_id: String,
+    /// Index of the first returned line within the run.
+    pub start: usize,
+    /// Lines in the whole run.
+    pub total: usize,
+    pub lines: Vec<LogLine>,
+}
+
+/// Lines `start..start + limit` of a run, across all of its segments.
+///
+/// `run_id` defaults to the newest run.
+pub fn read_run(dir: &Path, run_id: Option<&str>, start: usize, limit: usize) -> io::Result<LogPage> {
+    let mut runs = scan_runs(dir)?;
+    let run_id = match run_id {
+        Some(id) => id.to_string(),
+        None => runs
+            .keys()
+            .next_back()
+            .cloned()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No logs for this app yet"))?,
+    };
+    let run = runs
+        .remove(&run_id)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("No log for run {}", run_id)))?;
+
+    let mut total = 0;
+    let mut lines = Vec::new();
+    for (_, path) in &run.segments {
+        for line in BufReader::new(File::open(path)?).lines() {
+            let line = line?;
+            if total >= start && lines.len() < limit {
+                lines.push(parse_line(&line));
+            }
+            total += 1;
+        }
+    }
+    Ok(LogPage { run_id, start, total, lines })
+}
+
+fn parse_line(line: &str) -> LogLine {
+    let mut parts = line.splitn(3, '\t');
+    match (parts.next(), parts.next().and_then(LogStream::parse), parts.next()) {
+        (Some(timestamp), Some(stream), Some(text)) => LogLine {
+            timestamp: timestamp.to_string(),
+            stream,
+            text: text.to_string(),
+        },
+        // Not written by LogWriter, hand it back untouched
+        _ => LogLine {
+            timestamp: String::new(),
+            stream: LogStream::System,
+            text: line.to_string(),
+        },
+    }
+}
+
+/// App ids and run ids end up in paths, so keep them to one plain component.
+pub fn is_safe_name(name: &str) -> bool {
+    !name.is_empty()
+        && name != "."
+        && name != ".."
+        && !name.contains(['/', '\\', ':'])
+}
+
+/// Sortable, filename-safe id for a new run, e.g. `20261016T093015123Z`.
+pub fn new_run_id(now: SystemTime) -> String {
+    let (date, time, millis) = civil_parts(now);
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}{:03}Z",
+        date.0, date.1, date.2, time.0, time.1, time.2, millis
+    )
+}
+
+/// RFC 3339 in UTC with milliseconds, e.g. `2026-10-16T09:30:15.123Z`.
+pub fn format_timestamp(time: SystemTime) -> String {
+    let (date, time, millis) = civil_parts(time);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        date.0, date.1, date.2, time.0, time.1, time.2, millis
+    )
+}
+
+fn civil_parts(time: SystemTime) -> ((i64, u32, u32), (u64, u64, u64), u32) {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let days = (secs / 86400) as i64;
+    let rem = secs % 86400;
+    (civil_from_days(days), (rem / 3600, rem % 3600 / 60, rem % 60), since_epoch.subsec_millis())
+}
+
+/// Days since 1970-01-01 to (year, month, day), Howard Hinnant's algorithm.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ansi::StyledText;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("appctrl-logs-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn timestamps() {
+        let t = UNIX_EPOCH + Duration::from_millis(1_791_365_415_123);
+        assert_eq!(format_timestamp(t), "2026-10-07T09:30:15.123Z");
+        assert_eq!(new_run_id(t), "20261007T093015123Z");
+        assert_eq!(format_timestamp(UNIX_EPOCH), "1970-01-01T00:00:00.000Z");
+        // Leap day
+        assert_eq!(civil_from_days(19782), (2024, 2, 29));
+    }
+
+    #[test]
+    fn writes_and_reads_back_a_range() {
+        let dir = temp_dir("read");
+        let mut log = LogWriter::create(&dir, "run1", LogSettings::default()).unwrap();
+        log.write(SystemTime::now(), LogStream::System, "✓ Started").unwrap();
+        for i in 0..5 {
+            log.write(SystemTime::now(), LogStream::Stdout, &format!("line {}", i)).unwrap();
+        }
+        log.write(SystemTime::now(), LogStream::Stderr, "bad\tthing\nhappened").unwrap();
+
+        let page = read_run(&dir, Some("run1"), 2, 3).unwrap();
+        assert_eq!(page.total, 7);
+        let texts: Vec<&str> = page.lines.iter().map(|l| l.text.as_str()).collect();
+        assert_eq!(texts, ["line 1", "line 2", "line 3"]);
+
+        let last = read_run(&dir, None, 6, 10).unwrap();
+        assert_eq!(last.lines[0].stream, LogStream::Stderr);
+        assert_eq!(last.lines[0].text, "bad\tthing happened");
+        assert!(read_run(&dir, Some("missing"), 0, 10).is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn rotates_by_size() {
+        let dir = temp_dir("rotate");
+        let settings = LogSettings { max_file_size_kb: 1, ..LogSettings::default() };
+        let mut log = LogWriter::create(&dir, "run1", settings).unwrap();
+        let text = "x".repeat(300);
+        for _ in 0..10 {
+            log.write(SystemTime::now(), LogStream::Stdout, &text).unwrap();
+        }
+        let runs = list_runs(&dir).unwrap();
+        assert_eq!(runs.len(), 1);
+        assert!(runs[0].segments >= 3, "{} segments", runs[0].segments);
+        assert_eq!(read_run(&dir, Some("run1"), 0, 100).unwrap().total, 10);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn prunes_old_and_excess_runs() {
+        let dir = temp_dir("prune");
+        let settings = LogSettings { max_runs: 3, ..LogSettings::default() };
+        for run in ["r1", "r2", "r3", "r4"] {
+            let mut log = LogWriter::create(&dir, run, settings.clone()).unwrap();
+            log.write(SystemTime::now(), LogStream::Stdout, run).unwrap();
+        }
+        // r1 made room for r4
+        let ids: Vec<String> = list_runs(&dir).unwrap().into_iter().map(|r| r.run_id).collect();
+        assert_eq!(ids, ["r4", "r3", "r2"]);
+
+        let later = SystemTime::now() + Duration::from_secs(15 * 86400);
+        prune(&dir, &settings, later).unwrap();
+        assert!(list_runs(&dir).unwrap().is_empty());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn buffer_keeps_the_newest_lines() {
+        let mut buffer = LogBuffer::new(3);
+        let now = UNIX_EPOCH + Duration::from_secs(1_791_365_415);
+        for i in 1..=5 {
+            let line = StyledText::plain(&format!("line {}", i));
+            assert_eq!(buffer.push(now, LogStream::Stdout, Some("run1"), line, false).seq, i);
+        }
+        let seqs = |lines: Vec<LogRecord>| lines.iter().map(|l| l.seq).collect::<Vec<_>>();
+        assert_eq!(seqs(buffer.since(0, 100)), [3, 4, 5]);
+        assert_eq!(seqs(buffer.since(3, 100)), [4, 5]);
+        assert_eq!(seqs(buffer.since(3, 1)), [4]);
+        assert!(buffer.since(5, 100).is_empty());
+        assert!(buffer.since(99, 100).is_empty());
+        assert_eq!(
+            buffer.since(4, 100)[0],
+            LogRecord {
+                seq: 5,
+                timestamp: "2026-10-07T09:30:15.000Z".to_string(),
+                stream: LogStream::Stdout,
+                run_id: Some("run1".to_string()),
+                text: "line 5".to_string(),
+                spans: Vec::new(),
+                partial: false,
+            }
+        );
+    }
+
+    #[test]
+    fn buffer_drops_superseded_partial_lines() {
+        let mut buffer = LogBuffer::new(10);
+        let now = SystemTime::now();
+        buffer.push(now, LogStream::Stdout, None, StyledText::plain("10%"), true);
+        buffer.push(now, LogStream::Stderr, None, StyledText::plain("warning"), false);
+        buffer.push(now, LogStream::Stdout, None, StyledText::plain("50%"), true);
+        buffer.push(now, LogStream::Stdout, None, StyledText::plain("100%"), false);
+        let kept: Vec<(u64, &str)> = buffer.lines.iter().map(|l| (l.seq, l.text.as_str())).collect();
+        assert_eq!(kept, [(2, "warning"), (4, "100%")]);
+        assert_eq!(buffer.since(2, 10).len(), 1);
+        assert_eq!(buffer.since(3, 10)[0].seq, 4);
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(parse_segment_name("20261016T093015123Z-002.log"), Some(("20261016T093015123Z", 2)));
+        assert_eq!(parse_segment_name("notes.txt"), None);
+        assert!(is_safe_name("1718000000000"));
+        assert!(!is_safe_name("../config"));
+        assert!(!is_safe_name(".."));
+        assert!(!is_safe_name(""));
+    }
+}
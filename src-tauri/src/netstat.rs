@@ -0,0 +1,126 @@
+// Parsing of Windows `netstat -ano` output.
+//
+// Kept platform independent so it can be tested anywhere.
+
+use std::net::{IpAddr, SocketAddr};
+
+pub struct NetstatRow {
+    pub protocol: &'static str,
+    pub local: SocketAddr,
+    /// `None` for unconnected UDP sockets (`*:*`).
+    pub remote: Option<SocketAddr>,
+    /// TCP state as printed by netstat, empty for UDP.
+    pub state: String,
+    pub pid: u32,
+}
+
+impl NetstatRow {
+    /// Listening TCP sockets and unconnected UDP sockets.
+    ///
+    /// The state column is localized, so a listening TCP socket is
+    /// recognised by its remote port 0 instead of the word "LISTENING".
+    pub fn is_listening(&self) -> bool {
+        match self.remote {
+            Some(remote) => remote.port() == 0,
+            None => true,
+        }
+    }
+}
+
+/// Parse one row of `netstat -ano`; headers and blank lines give `None`.
+///
+/// ```text
+///   TCP    0.0.0.0:80           0.0.0.0:0              LISTENING       1234
+///   TCP    [::1]:3000           [::1]:52144            ESTABLISHED     5678
+///   UDP    [fe80::1%12]:5353    *:*                                    999
+/// ```
+pub fn parse_netstat_line(line: &str) -> Option<NetstatRow> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    let protocol = match *parts.first()? {
+        "TCP" => "TCP",
+        "UDP" => "UDP",
+        _ => return None,
+    };
+    // UDP doesn't have a "State" column, PID is at index 3
+    let (state, pid) = match (protocol, parts.len()) {
+        ("TCP", n) if n >= 5 => (parts[3], parts[4]),
+        ("UDP", n) if n >= 4 => ("", parts[3]),
+        _ => return None,
+    };
+    let local = parse_endpoint(parts[1])?;
+    let remote = if parts[2] == "*:*" { None } else { Some(parse_endpoint(parts[2])?) };
+    let state = if protocol == "TCP" && remote.map(|r| r.port()) == Some(0) {
+        "LISTENING".to_string()
+    } else {
+        state.to_string()
+    };
+
+    Some(NetstatRow {
+        protocol,
+        local,
+        remote,
+        state,
+        pid: pid.parse().ok()?,
+    })
+}
+
+/// `a.b.c.d:port` or `[v6%scope]:port`; the zone index is dropped.
+pub fn parse_endpoint(s: &str) -> Option<SocketAddr> {
+    let (addr, port) = s.rsplit_once(':')?;
+    let addr = addr.strip_prefix('[').and_then(|a| a.strip_suffix(']')).unwrap_or(addr);
+    let addr = addr.split('%').next()?;
+    let ip: IpAddr = addr.parse().ok()?;
+    Some(SocketAddr::new(ip, port.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ipv4_listening() {
+        let row = parse_netstat_line("  TCP    0.0.0.0:80             0.0.0.0:0              LISTENING       1234").unwrap();
+        assert_eq!(row.local, "0.0.0.0:80".parse().unwrap());
+        assert_eq!(row.state, "LISTENING");
+        assert_eq!(row.pid, 1234);
+        assert!(row.is_listening());
+    }
+
+    #[test]
+    fn ipv6_and_localized_state() {
+        // German Windows prints "ABHÖREN" for LISTENING
+        let row = parse_netstat_line("  TCP    [::]:445               [::]:0                 ABHÖREN         4").unwrap();
+        assert_eq!(row.local, "[::]:445".parse().unwrap());
+        assert_eq!(row.state, "LISTENING");
+
+        let row = parse_netstat_line("  TCP    [::1]:3000             [::1]:52144            ESTABLISHED     5678").unwrap();
+        assert_eq!(row.local, "[::1]:3000".parse().unwrap());
+        assert_eq!(row.remote, Some("[::1]:52144".parse().unwrap()));
+        assert_eq!(row.state, "ESTABLISHED");
+        assert!(!row.is_listening());
+    }
+
+    #[test]
+    fn udp_rows() {
+        let row = parse_netstat_line("  UDP    [fe80::1%12]:5353      *:*                                    999").unwrap();
+        assert_eq!(row.local, "[fe80::1]:5353".parse().unwrap());
+        assert_eq!(row.remote, None);
+        assert_eq!(row.state, "");
+        assert!(row.is_listening());
+    }
+
+    #[test]
+    fn loopback_and_any_are_distinct() {
+        let any = parse_netstat_line("  TCP    0.0.0.0:8080  0.0.0.0:0  LISTENING  10").unwrap();
+        let lo = parse_netstat_line("  TCP    127.0.0.1:8080  0.0.0.0:0  LISTENING  10").unwrap();
+        assert_ne!(any.local, lo.local);
+        assert_eq!(any.local.port(), lo.local.port());
+    }
+
+    #[test]
+    fn headers_are_skipped() {
+        assert!(parse_netstat_line("Active Connections").is_none());
+        assert!(parse_netstat_line("  Proto  Local Address          Foreign Address        State           PID").is_none());
+        assert!(parse_netstat_line("").is_none());
+    }
+}
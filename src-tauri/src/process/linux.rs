@@ -0,0 +1,73 @@
+// /proc + signals backend.
+
+use super::{descendants, ProcessBackend, ProcessError, ProcessSnapshot, StopSignal};
+use crate::procfs;
+use std::path::Path;
+
+pub struct LinuxBackend;
+
+impl ProcessBackend for LinuxBackend {
+    fn list(&self) -> Result<Vec<ProcessSnapshot>, ProcessError> {
+        let entries = procfs::read_processes(Path::new("/proc")).map_err(|e| ProcessError::Os {
+            pid: None,
+            code: e.raw_os_error().unwrap_or(0),
+        })?;
+        let users = procfs::read_user_names(Path::new("/etc/passwd"));
+
+        Ok(entries
+            .into_iter()
+            .map(|p| ProcessSnapshot {
+                pid: p.pid,
+                parent_pid: p.ppid,
+                name: p.name,
+                memory_kb: p.rss_kb,
+                command_line: Some(p.command_line),
+                user: p.uid.map(|uid| users.get(&uid).cloned().unwrap_or_else(|| uid.to_string())),
+                kernel_thread: p.kernel_thread,
+            })
+            .collect())
+    }
+
+    fn kill(&self, pid: u32) -> Result<(), ProcessError> {
+        send_signal(pid, libc::SIGKILL)
+    }
+
+    fn request_stop(&self, pid: u32, signal: StopSignal) -> Result<StopSignal, ProcessError> {
+        let signo = match signal {
+            StopSignal::Term => libc::SIGTERM,
+            StopSignal::Int => libc::SIGINT,
+            StopSignal::CtrlBreak | StopSignal::Close => return Err(ProcessError::Unsupported),
+        };
+        let processes = self.list()?;
+        // Like a terminal signalling its foreground group: the shell wrapper
+        // and everything it started get the signal, not just the root
+        send_signal(pid, signo)?;
+        for child in descendants(&processes, pid) {
+            let _ = send_signal(child, signo);
+        }
+        Ok(signal)
+    }
+}
+
+/// Send `signal` to `pid`, mapping errno to `ProcessError`.
+pub fn send_signal(pid: u32, signal: i32) -> Result<(), ProcessError> {
+    // pid 0 and negative pids address process groups, never do that here
+    if pid == 0 || pid > i32::MAX as u32 {
+        return Err(ProcessError::NoSuchProcess(pid));
+    }
+    // A zombie still accepts signals but has already exited
+    if let Ok(stat) = std::fs::read_to_string(format!("/proc/{}/stat", pid)) {
+        if matches!(procfs::process_state(&stat), Some('Z') | Some('X')) {
+            return Err(ProcessError::AlreadyExited(pid));
+        }
+    }
+
+    if unsafe { libc::kill(pid as i32, signal) } == 0 {
+        return Ok(());
+    }
+    match std::io::Error::last_os_error().raw_os_error() {
+        Some(libc::EPERM) => Err(ProcessError::AccessDenied(pid)),
+        Some(libc::ESRCH) => Err(ProcessError::NoSuchProcess(pid)),
+        code => Err(ProcessError::Os { pid: Some(pid), code: code.unwrap_or(0) }),
+    }
+}
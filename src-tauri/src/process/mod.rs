@@ -0,0 +1,476 @@
+// Native process enumeration and termination.
+//
+// `ProcessBackend` hides the OS specifics (ToolHelp32/OpenProcess on
+// Windows, /proc and signals on Linux) and reports failures as
+// `ProcessError` instead of scraping localized tasklist/taskkill output.
+
+use std::fmt;
+use std::process::ExitStatus;
+use std::time::Duration;
+
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(windows)]
+mod windows;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProcessError {
+    AccessDenied(u32),
+    NoSuchProcess(u32),
+    AlreadyExited(u32),
+    Unsupported,
+    /// Any other OS error, `code` is errno / GetLastError.
+    Os { pid: Option<u32>, code: i32 },
+}
+
+impl ProcessError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ProcessError::AccessDenied(_) => "accessDenied",
+            ProcessError::NoSuchProcess(_) => "noSuchProcess",
+            ProcessError::AlreadyExited(_) => "alreadyExited",
+            ProcessError::Unsupported => "unsupported",
+            ProcessError::Os { .. } => "os",
+        }
+    }
+
+    pub fn pid(&self) -> Option<u32> {
+        match self {
+            ProcessError::AccessDenied(pid)
+            | ProcessError::NoSuchProcess(pid)
+            | ProcessError::AlreadyExited(pid) => Some(*pid),
+            ProcessError::Unsupported => None,
+            ProcessError::Os { pid, .. } => *pid,
+        }
+    }
+
+    /// The process is gone either way, which callers trying to kill it
+    /// usually treat as success.
+    pub fn is_gone(&self) -> bool {
+        matches!(self, ProcessError::NoSuchProcess(_) | ProcessError::AlreadyExited(_))
+    }
+}
+
+impl fmt::Display for ProcessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProcessError::AccessDenied(pid) => write!(f, "Access denied for process {}", pid),
+            ProcessError::NoSuchProcess(pid) => write!(f, "No such process: {}", pid),
+            ProcessError::AlreadyExited(pid) => write!(f, "Process {} has already exited", pid),
+            ProcessError::Unsupported => write!(f, "Operation not supported for this process"),
+            ProcessError::Os { pid: Some(pid), code } => {
+                write!(f, "Process {}: {}", pid, std::io::Error::from_raw_os_error(*code))
+            }
+            ProcessError::Os { pid: None, code } => {
+                write!(f, "{}", std::io::Error::from_raw_os_error(*code))
+            }
+        }
+    }
+}
+
+// Sent to the frontend as { kind, pid, message }
+impl serde::Serialize for ProcessError {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut s = serializer.serialize_struct("ProcessError", 3)?;
+        s.serialize_field("kind", self.kind())?;
+        s.serialize_field("pid", &self.pid())?;
+        s.serialize_field("message", &self.to_string())?;
+        s.end()
+    }
+}
+
+impl From<ProcessError> for String {
+    fn from(e: ProcessError) -> Self {
+        e.to_string()
+    }
+}
+
+/// Graceful stop request sent before falling back to a forced kill.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopSignal {
+    Term,
+    Int,
+    /// CTRL_BREAK_EVENT to the app's console process group (Windows)
+    CtrlBreak,
+    /// WM_CLOSE to the app's top-level windows (Windows)
+    Close,
+}
+
+impl StopSignal {
+    /// Accepts the names shown by `name()`, case-insensitive, with or
+    /// without the SIG / CTRL_ / WM_ prefix.
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim().to_ascii_uppercase();
+        let s = s.strip_prefix("SIG").or_else(|| s.strip_prefix("WM_")).unwrap_or(&s);
+        match s.replace(['_', '+', '-', ' '], "").as_str() {
+            "TERM" => Some(StopSignal::Term),
+            "INT" => Some(StopSignal::Int),
+            "CTRLBREAK" | "BREAK" => Some(StopSignal::CtrlBreak),
+            "CLOSE" => Some(StopSignal::Close),
+            _ => None,
+        }
+    }
+
+    pub fn default_for_os() -> Self {
+        if cfg!(windows) {
+            StopSignal::CtrlBreak
+        } else {
+            StopSignal::Term
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            StopSignal::Term => "SIGTERM",
+            StopSignal::Int => "SIGINT",
+            StopSignal::CtrlBreak => "CTRL_BREAK",
+            StopSignal::Close => "WM_CLOSE",
+        }
+    }
+}
+
+impl fmt::Display for StopSignal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// How a managed app's process ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExitInfo {
+    pub code: Option<i32>,
+    /// Terminating signal on Unix.
+    pub signal: Option<i32>,
+    pub runtime: Duration,
+}
+
+impl ExitInfo {
+    pub fn new(status: &ExitStatus, runtime: Duration) -> Self {
+        #[cfg(unix)]
+        let signal = std::os::unix::process::ExitStatusExt::signal(status);
+        #[cfg(not(unix))]
+        let signal = None;
+        Self { code: status.code(), signal, runtime }
+    }
+
+    pub fn success(&self) -> bool {
+        self.code == Some(0)
+    }
+
+    pub fn signal_name(&self) -> Option<&'static str> {
+        self.signal.and_then(signal_name)
+    }
+
+    /// Log line for the app's output pane.
+    pub fn describe(&self) -> String {
+        let runtime = format!("after {:.1}s", self.runtime.as_secs_f32());
+        match (self.code, self.signal) {
+            (Some(0), _) => format!("✓ Process exited successfully {}", runtime),
+            // NTSTATUS crash codes read better in hex
+            (Some(code), _) if cfg!(windows) && code < 0 => {
+                format!("⚠ Process exited with code: {} (0x{:08X}) {}", code, code, runtime)
+            }
+            (Some(code), _) => format!("⚠ Process exited with code: {} {}", code, runtime),
+            (None, Some(signal)) => match signal_name(signal) {
+                Some(name) => format!("⚠ Process killed by {} (signal {}) {}", name, signal, runtime),
+                None => format!("⚠ Process killed by signal {} {}", signal, runtime),
+            },
+            (None, None) => format!("⚠ Process exited {}", runtime),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn signal_name(signal: i32) -> Option<&'static str> {
+    Some(match signal {
+        libc::SIGHUP => "SIGHUP",
+        libc::SIGINT => "SIGINT",
+        libc::SIGQUIT => "SIGQUIT",
+        libc::SIGILL => "SIGILL",
+        libc::SIGTRAP => "SIGTRAP",
+        libc::SIGABRT => "SIGABRT",
+        libc::SIGBUS => "SIGBUS",
+        libc::SIGFPE => "SIGFPE",
+        libc::SIGKILL => "SIGKILL",
+        libc::SIGUSR1 => "SIGUSR1",
+        libc::SIGSEGV => "SIGSEGV",
+        libc::SIGUSR2 => "SIGUSR2",
+        libc::SIGPIPE => "SIGPIPE",
+        libc::SIGALRM => "SIGALRM",
+        libc::SIGTERM => "SIGTERM",
+        _ => return None,
+    })
+}
+
+#[cfg(not(unix))]
+fn signal_name(_signal: i32) -> Option<&'static str> {
+    None
+}
+
+pub struct ProcessSnapshot {
+    pub pid: u32,
+    pub parent_pid: u32,
+    pub name: String,
+    pub memory_kb: Option<u64>,
+    pub command_line: Option<String>,
+    pub user: Option<String>,
+    pub kernel_thread: bool,
+}
+
+pub trait ProcessBackend: Send + Sync {
+    fn list(&self) -> Result<Vec<ProcessSnapshot>, ProcessError>;
+
+    /// Forcefully terminate a single process.
+    fn kill(&self, pid: u32) -> Result<(), ProcessError>;
+
+    /// Ask the process tree rooted at `pid` to exit on its own.
+    ///
+    /// Returns the request that was actually delivered, which can differ
+    /// from `signal` when the backend has to fall back (e.g. a Windows GUI
+    /// app has no console to receive Ctrl+Break).
+    fn request_stop(&self, _pid: u32, _signal: StopSignal) -> Result<StopSignal, ProcessError> {
+        Err(ProcessError::Unsupported)
+    }
+
+    /// Kill `pid` and all of its descendants, deepest first.
+    ///
+    /// Children that are already gone are ignored; the first other failure
+    /// is reported after everything has been tried.
+    fn kill_tree(&self, pid: u32) -> Result<(), ProcessError> {
+        let processes = self.list()?;
+        let mut first_error = None;
+        for child in descendants(&processes, pid).into_iter().rev() {
+            if let Err(e) = self.kill(child) {
+                if !e.is_gone() && first_error.is_none() {
+                    first_error = Some(e);
+                }
+            }
+        }
+        self.kill(pid)?;
+        first_error.map_or(Ok(()), Err)
+    }
+
+    fn find_by_name(&self, name: &str) -> Result<Vec<u32>, ProcessError> {
+        Ok(self
+            .list()?
+            .into_iter()
+            .filter(|p| name_matches(&p.name, name))
+            .map(|p| p.pid)
+            .collect())
+    }
+
+    /// Kill every process called `name`, returning how many were killed.
+    fn kill_by_name(&self, name: &str) -> Result<usize, ProcessError> {
+        let mut killed = 0;
+        let mut first_error = None;
+        for pid in self.find_by_name(name)? {
+            match self.kill(pid) {
+                Ok(()) => killed += 1,
+                Err(e) if e.is_gone() => {}
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        first_error.map_or(Ok(killed), Err)
+    }
+}
+
+/// All descendants of `root` in breadth-first order.
+pub fn descendants(processes: &[ProcessSnapshot], root: u32) -> Vec<u32> {
+    let mut result = Vec::new();
+    let mut queue = vec![root];
+    while let Some(parent) = queue.pop() {
+        for p in processes {
+            // pid 0 is its own parent on Windows
+            if p.parent_pid == parent && p.pid != parent && p.pid != root && !result.contains(&p.pid) {
+                result.push(p.pid);
+                queue.insert(0, p.pid);
+            }
+        }
+    }
+    result
+}
+
+/// Compare a process name with an executable file name.
+///
+/// Windows names are case-insensitive. Linux `comm` is cut to 15 bytes, so
+/// a 15 byte name also matches longer executable names it is a prefix of.
+pub fn name_matches(process_name: &str, wanted: &str) -> bool {
+    if cfg!(windows) {
+        process_name.eq_ignore_ascii_case(wanted)
+    } else {
+        process_name == wanted || (process_name.len() == 15 && wanted.starts_with(process_name))
+    }
+}
+
+#[cfg(windows)]
+pub fn native() -> &'static dyn ProcessBackend {
+    &windows::WindowsBackend
+}
+
+#[cfg(target_os = "linux")]
+pub fn native() -> &'static dyn ProcessBackend {
+    &linux::LinuxBackend
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+pub fn native() -> &'static dyn ProcessBackend {
+    &UnsupportedBackend
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+struct UnsupportedBackend;
+
+#[cfg(not(any(windows, target_os = "linux")))]
+impl ProcessBackend for UnsupportedBackend {
+    fn list(&self) -> Result<Vec<ProcessSnapshot>, ProcessError> {
+        Err(ProcessError::Unsupported)
+    }
+
+    fn kill(&self, _pid: u32) -> Result<(), ProcessError> {
+        Err(ProcessError::Unsupported)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    fn snapshot(pid: u32, parent_pid: u32, name: &str) -> ProcessSnapshot {
+        ProcessSnapshot {
+            pid,
+            parent_pid,
+            name: name.to_string(),
+            memory_kb: None,
+            command_line: None,
+            user: None,
+            kernel_thread: false,
+        }
+    }
+
+    // Fake backend that records kills and fails for configured pids
+    struct FakeBackend {
+        processes: Vec<(u32, u32, &'static str)>,
+        failures: Vec<ProcessError>,
+        killed: Mutex<Vec<u32>>,
+    }
+
+    impl ProcessBackend for FakeBackend {
+        fn list(&self) -> Result<Vec<ProcessSnapshot>, ProcessError> {
+            Ok(self.processes.iter().map(|&(pid, ppid, name)| snapshot(pid, ppid, name)).collect())
+        }
+
+        fn kill(&self, pid: u32) -> Result<(), ProcessError> {
+            if let Some(e) = self.failures.iter().find(|e| e.pid() == Some(pid)) {
+                return Err(e.clone());
+            }
+            self.killed.lock().unwrap().push(pid);
+            Ok(())
+        }
+    }
+
+    fn fake(failures: Vec<ProcessError>) -> FakeBackend {
+        FakeBackend {
+            processes: vec![
+                (1, 0, "init"),
+                (10, 1, "cmd.exe"),
+                (11, 10, "node.exe"),
+                (12, 11, "esbuild.exe"),
+                (13, 10, "node.exe"),
+                (20, 1, "other.exe"),
+            ],
+            failures,
+            killed: Mutex::new(Vec::new()),
+        }
+    }
+
+    #[test]
+    fn descendants_are_breadth_first() {
+        let backend = fake(vec![]);
+        assert_eq!(descendants(&backend.list().unwrap(), 10), [11, 13, 12]);
+        assert!(descendants(&backend.list().unwrap(), 20).is_empty());
+    }
+
+    #[test]
+    fn kill_tree_kills_children_first() {
+        let backend = fake(vec![]);
+        backend.kill_tree(10).unwrap();
+        assert_eq!(*backend.killed.lock().unwrap(), [12, 13, 11, 10]);
+    }
+
+    #[test]
+    fn kill_tree_ignores_exited_children_but_reports_denied() {
+        let backend = fake(vec![ProcessError::AlreadyExited(12)]);
+        assert_eq!(backend.kill_tree(10), Ok(()));
+
+        let backend = fake(vec![ProcessError::AccessDenied(13)]);
+        assert_eq!(backend.kill_tree(10), Err(ProcessError::AccessDenied(13)));
+        assert!(backend.killed.lock().unwrap().contains(&10));
+    }
+
+    #[test]
+    fn kill_by_name_counts_kills() {
+        let backend = fake(vec![ProcessError::NoSuchProcess(13)]);
+        let wanted = if cfg!(windows) { "NODE.EXE" } else { "node.exe" };
+        assert_eq!(backend.kill_by_name(wanted), Ok(1));
+        assert_eq!(backend.kill_by_name("missing.exe"), Ok(0));
+    }
+
+    #[test]
+    fn name_matching() {
+        assert!(name_matches("node", "node"));
+        assert!(!name_matches("node", "nodemon"));
+        if !cfg!(windows) {
+            assert!(name_matches("my-long-server-", "my-long-server-binary"));
+        }
+    }
+
+    #[test]
+    fn stop_signal_names() {
+        assert_eq!(StopSignal::parse("SIGTERM"), Some(StopSignal::Term));
+        assert_eq!(StopSignal::parse("int"), Some(StopSignal::Int));
+        assert_eq!(StopSignal::parse("Ctrl+Break"), Some(StopSignal::CtrlBreak));
+        assert_eq!(StopSignal::parse("CTRL_BREAK"), Some(StopSignal::CtrlBreak));
+        assert_eq!(StopSignal::parse("wm_close"), Some(StopSignal::Close));
+        assert_eq!(StopSignal::parse("SIGKILL"), None);
+        for signal in [StopSignal::Term, StopSignal::Int, StopSignal::CtrlBreak, StopSignal::Close] {
+            assert_eq!(StopSignal::parse(signal.name()), Some(signal));
+        }
+    }
+
+    #[test]
+    fn exit_descriptions() {
+        let runtime = Duration::from_millis(1500);
+        let exit = |code, signal| ExitInfo { code, signal, runtime };
+        assert!(exit(Some(0), None).success());
+        assert_eq!(exit(Some(0), None).describe(), "✓ Process exited successfully after 1.5s");
+        assert_eq!(exit(Some(3), None).describe(), "⚠ Process exited with code: 3 after 1.5s");
+        assert!(!exit(None, Some(9)).success());
+        if cfg!(unix) {
+            assert_eq!(exit(None, Some(9)).signal_name(), Some("SIGKILL"));
+            assert_eq!(exit(None, Some(15)).describe(), "⚠ Process killed by SIGTERM (signal 15) after 1.5s");
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn exit_info_from_status() {
+        use std::os::unix::process::ExitStatusExt;
+        let killed = ExitInfo::new(&ExitStatus::from_raw(libc::SIGKILL), Duration::ZERO);
+        assert_eq!((killed.code, killed.signal), (None, Some(libc::SIGKILL)));
+        let failed = ExitInfo::new(&ExitStatus::from_raw(2 << 8), Duration::ZERO);
+        assert_eq!((failed.code, failed.signal), (Some(2), None));
+    }
+
+    #[test]
+    fn errors_serialize_with_kind() {
+        let json = serde_json::to_value(ProcessError::AccessDenied(4)).unwrap();
+        assert_eq!(json["kind"], "accessDenied");
+        assert_eq!(json["pid"], 4);
+        assert_eq!(json["message"], "Access denied for process 4");
+        assert_eq!(String::from(ProcessError::AlreadyExited(9)), "Process 9 has already exited");
+    }
+}
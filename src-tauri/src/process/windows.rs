@@ -0,0 +1,182 @@
+// ToolHelp32 / OpenProcess backend.
+
+use super::{descendants, ProcessBackend, ProcessError, ProcessSnapshot, StopSignal};
+use std::sync::Mutex;
+use winapi::shared::minwindef::{BOOL, DWORD, FALSE, LPARAM, TRUE};
+use winapi::shared::windef::HWND;
+use winapi::shared::winerror::{ERROR_ACCESS_DENIED, ERROR_INVALID_PARAMETER};
+use winapi::um::errhandlingapi::GetLastError;
+use winapi::um::handleapi::{CloseHandle, INVALID_HANDLE_VALUE};
+use winapi::um::minwinbase::STILL_ACTIVE;
+use winapi::um::processthreadsapi::{GetExitCodeProcess, OpenProcess, TerminateProcess};
+use winapi::um::psapi::{GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS};
+use winapi::um::tlhelp32::{
+    CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W, TH32CS_SNAPPROCESS,
+};
+use winapi::um::wincon::{AttachConsole, FreeConsole, GenerateConsoleCtrlEvent, CTRL_BREAK_EVENT};
+use winapi::um::winnt::{HANDLE, PROCESS_QUERY_LIMITED_INFORMATION, PROCESS_TERMINATE, PROCESS_VM_READ};
+use winapi::um::winuser::{EnumWindows, GetWindowThreadProcessId, PostMessageW, WM_CLOSE};
+
+pub struct WindowsBackend;
+
+/// Held while AppCtrl is attached to an app's console. There is only one
+/// console per process, so two stops at once would detach each other.
+static CONSOLE: Mutex<()> = Mutex::new(());
+
+impl ProcessBackend for WindowsBackend {
+    fn list(&self) -> Result<Vec<ProcessSnapshot>, ProcessError> {
+        let mut processes = Vec::new();
+        unsafe {
+            let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0);
+            if snapshot == INVALID_HANDLE_VALUE {
+                return Err(os_error(None, GetLastError()));
+            }
+
+            let mut entry: PROCESSENTRY32W = std::mem::zeroed();
+            entry.dwSize = std::mem::size_of::<PROCESSENTRY32W>() as DWORD;
+            let mut ok = Process32FirstW(snapshot, &mut entry);
+            while ok != 0 {
+                let len = entry.szExeFile.iter().position(|&c| c == 0).unwrap_or(entry.szExeFile.len());
+                let pid = entry.th32ProcessID;
+                let name = if pid == 0 {
+                    // Same name tasklist uses, so existing filters keep working
+                    "System Idle Process".to_string()
+                } else {
+                    String::from_utf16_lossy(&entry.szExeFile[..len])
+                };
+                processes.push(ProcessSnapshot {
+                    pid,
+                    parent_pid: entry.th32ParentProcessID,
+                    name,
+                    memory_kb: working_set_kb(pid),
+                    command_line: None,
+                    user: None,
+                    kernel_thread: false,
+                });
+                ok = Process32NextW(snapshot, &mut entry);
+            }
+            CloseHandle(snapshot);
+        }
+        Ok(processes)
+    }
+
+    fn kill(&self, pid: u32) -> Result<(), ProcessError> {
+        unsafe {
+            let handle = OpenProcess(PROCESS_TERMINATE | PROCESS_QUERY_LIMITED_INFORMATION, FALSE, pid);
+            if handle.is_null() {
+                return Err(os_error(Some(pid), GetLastError()));
+            }
+            let result = if has_exited(handle) {
+                Err(ProcessError::AlreadyExited(pid))
+            } else if TerminateProcess(handle, 1) == 0 {
+                let code = GetLastError();
+                // TerminateProcess fails with access denied on an exiting process
+                if has_exited(handle) {
+                    Err(ProcessError::AlreadyExited(pid))
+                } else {
+                    Err(os_error(Some(pid), code))
+                }
+            } else {
+                Ok(())
+            };
+            CloseHandle(handle);
+            result
+        }
+    }
+
+    fn request_stop(&self, pid: u32, signal: StopSignal) -> Result<StopSignal, ProcessError> {
+        match signal {
+            StopSignal::CtrlBreak => match send_ctrl_break(pid) {
+                Ok(()) => Ok(StopSignal::CtrlBreak),
+                // GUI apps have no console; closing their windows is the
+                // closest graceful equivalent
+                Err(_) => self.request_stop(pid, StopSignal::Close),
+            },
+            StopSignal::Close => {
+                let mut pids = descendants(&self.list()?, pid);
+                pids.push(pid);
+                if post_close(&pids) == 0 {
+                    return Err(ProcessError::Unsupported);
+                }
+                Ok(StopSignal::Close)
+            }
+            StopSignal::Term | StopSignal::Int => Err(ProcessError::Unsupported),
+        }
+    }
+}
+
+/// Ctrl+Break to the console process group led by `pid`.
+///
+/// Apps are started with CREATE_NEW_PROCESS_GROUP, so their group id is
+/// their pid and the event doesn't reach AppCtrl itself.
+fn send_ctrl_break(pid: u32) -> Result<(), ProcessError> {
+    let _console = CONSOLE.lock().unwrap();
+    unsafe {
+        // A process can only be attached to one console at a time
+        FreeConsole();
+        if AttachConsole(pid) == 0 {
+            return Err(os_error(Some(pid), GetLastError()));
+        }
+        let ok = GenerateConsoleCtrlEvent(CTRL_BREAK_EVENT, pid);
+        let code = GetLastError();
+        FreeConsole();
+        if ok == 0 {
+            return Err(os_error(Some(pid), code));
+        }
+    }
+    Ok(())
+}
+
+/// Post WM_CLOSE to every top-level window owned by `pids`, returning how
+/// many windows were found.
+fn post_close(pids: &[u32]) -> usize {
+    struct Search<'a> {
+        pids: &'a [u32],
+        posted: usize,
+    }
+
+    unsafe extern "system" fn visit(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        let search = &mut *(lparam as *mut Search);
+        let mut owner: DWORD = 0;
+        GetWindowThreadProcessId(hwnd, &mut owner);
+        if search.pids.contains(&owner) && PostMessageW(hwnd, WM_CLOSE, 0, 0) != 0 {
+            search.posted += 1;
+        }
+        TRUE
+    }
+
+    let mut search = Search { pids, posted: 0 };
+    unsafe {
+        EnumWindows(Some(visit), &mut search as *mut Search as LPARAM);
+    }
+    search.posted
+}
+
+unsafe fn has_exited(handle: HANDLE) -> bool {
+    let mut code: DWORD = 0;
+    GetExitCodeProcess(handle, &mut code) != 0 && code != STILL_ACTIVE
+}
+
+/// Working set in KB, the value tasklist shows as "Mem Usage".
+fn working_set_kb(pid: u32) -> Option<u64> {
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION | PROCESS_VM_READ, FALSE, pid);
+        if handle.is_null() {
+            return None;
+        }
+        let mut counters: PROCESS_MEMORY_COUNTERS = std::mem::zeroed();
+        let size = std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as DWORD;
+        let ok = GetProcessMemoryInfo(handle, &mut counters, size);
+        CloseHandle(handle);
+        (ok != 0).then_some(counters.WorkingSetSize as u64 / 1024)
+    }
+}
+
+fn os_error(pid: Option<u32>, code: DWORD) -> ProcessError {
+    match (pid, code) {
+        (Some(pid), ERROR_ACCESS_DENIED) => ProcessError::AccessDenied(pid),
+        // OpenProcess reports an unknown pid as an invalid parameter
+        (Some(pid), ERROR_INVALID_PARAMETER) => ProcessError::NoSuchProcess(pid),
+        _ => ProcessError::Os { pid, code: code as i32 },
+    }
+}
@@ -0,0 +1,219 @@
+// Process enumeration from /proc (Linux).
+//
+// Everything takes the procfs root as a parameter so the parsers can be
+// pointed at a fixture directory in tests.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Kernel thread flag in /proc/<pid>/stat (PF_KTHREAD).
+const PF_KTHREAD: u64 = 0x0020_0000;
+
+pub struct ProcEntry {
+    pub pid: u32,
+    pub ppid: u32,
+    pub name: String,
+    pub rss_kb: Option<u64>,
+    pub command_line: String,
+    pub uid: Option<u32>,
+    pub kernel_thread: bool,
+}
+
+/// Read every process under `root` (normally `/proc`).
+///
+/// Processes that exit while we are reading them are skipped.
+pub fn read_processes(root: &Path) -> std::io::Result<Vec<ProcEntry>> {
+    let mut processes = Vec::new();
+    for entry in std::fs::read_dir(root)?.flatten() {
+        let pid = match entry.file_name().to_str().and_then(|s| s.parse::<u32>().ok()) {
+            Some(pid) => pid,
+            None => continue,
+        };
+        if let Some(p) = read_process(&entry.path(), pid) {
+            processes.push(p);
+        }
+    }
+    Ok(processes)
+}
+
+fn read_process(dir: &Path, pid: u32) -> Option<ProcEntry> {
+    let status = std::fs::read_to_string(dir.join("status")).ok()?;
+    let status = parse_status(&status);
+    let command_line = std::fs::read(dir.join("cmdline"))
+        .map(|b| parse_cmdline(&b))
+        .unwrap_or_default();
+    let kernel_thread = std::fs::read_to_string(dir.join("stat"))
+        .map(|s| is_kernel_thread(&s))
+        .unwrap_or(false);
+
+    Some(ProcEntry {
+        pid,
+        ppid: status.ppid,
+        name: display_name(&status.name, &command_line),
+        rss_kb: status.rss_kb,
+        command_line,
+        uid: status.uid,
+        kernel_thread,
+    })
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct ProcStatus {
+    pub name: String,
+    pub ppid: u32,
+    pub rss_kb: Option<u64>,
+    pub uid: Option<u32>,
+}
+
+/// Extract `Name`, `PPid`, `VmRSS` (kB) and the real `Uid` from
+/// /proc/<pid>/status.
+pub fn parse_status(text: &str) -> ProcStatus {
+    let mut status = ProcStatus::default();
+    for line in text.lines() {
+        if let Some((key, value)) = line.split_once(':') {
+            let value = value.trim();
+            let first = value.split_whitespace().next();
+            match key {
+                "Name" => status.name = value.to_string(),
+                "PPid" => status.ppid = first.and_then(|v| v.parse().ok()).unwrap_or(0),
+                "VmRSS" => status.rss_kb = first.and_then(|v| v.parse().ok()),
+                "Uid" => status.uid = first.and_then(|v| v.parse().ok()),
+                _ => {}
+            }
+        }
+    }
+    status
+}
+
+/// Single-letter state from /proc/<pid>/stat (`R`, `S`, `Z`, ...).
+pub fn process_state(stat: &str) -> Option<char> {
+    let rest = &stat[stat.rfind(')')? + 1..];
+    rest.split_whitespace().next()?.chars().next()
+}
+
+/// /proc/<pid>/cmdline is NUL separated with a trailing NUL.
+pub fn parse_cmdline(bytes: &[u8]) -> String {
+    bytes
+        .split(|&b| b == 0)
+        .filter(|part| !part.is_empty())
+        .map(|part| String::from_utf8_lossy(part))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Check the PF_KTHREAD bit in the `flags` field of /proc/<pid>/stat.
+pub fn is_kernel_thread(stat: &str) -> bool {
+    // comm may contain spaces and parens, so fields start after the last ')'
+    let rest = match stat.rfind(')') {
+        Some(i) => &stat[i + 1..],
+        None => return false,
+    };
+    // state ppid pgrp session tty_nr tpgid flags
+    rest.split_whitespace()
+        .nth(6)
+        .and_then(|f| f.parse::<u64>().ok())
+        .map(|flags| flags & PF_KTHREAD != 0)
+        .unwrap_or(false)
+}
+
+/// `comm` is truncated to 15 bytes; prefer the executable name from the
+/// command line when it extends the truncated name.
+fn display_name(comm: &str, command_line: &str) -> String {
+    let argv0 = command_line.split(' ').next().unwrap_or("");
+    let base = argv0.rsplit('/').next().unwrap_or("");
+    if comm.len() >= 15 && base.len() > comm.len() && base.starts_with(comm) {
+        base.to_string()
+    } else {
+        comm.to_string()
+    }
+}
+
+/// Map uids to user names using an /etc/passwd formatted file.
+pub fn read_user_names(passwd: &Path) -> HashMap<u32, String> {
+    std::fs::read_to_string(passwd)
+        .map(|text| parse_passwd(&text))
+        .unwrap_or_default()
+}
+
+pub fn parse_passwd(text: &str) -> HashMap<u32, String> {
+    let mut users = HashMap::new();
+    for line in text.lines() {
+        let mut fields = line.split(':');
+        if let (Some(name), Some(_), Some(uid)) = (fields.next(), fields.next(), fields.next()) {
+            if let Ok(uid) = uid.parse::<u32>() {
+                users.entry(uid).or_insert_with(|| name.to_string());
+            }
+        }
+    }
+    users
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_fields() {
+        let status = "Name:\tnode\nUmask:\t0022\nState:\tS (sleeping)\nPPid:\t812\nUid:\t1000\t1000\t1000\t1000\nVmRSS:\t   52344 kB\n";
+        assert_eq!(
+            parse_status(status),
+            ProcStatus { name: "node".to_string(), ppid: 812, rss_kb: Some(52344), uid: Some(1000) }
+        );
+        assert_eq!(parse_status("Name:\tkthreadd\nUid:\t0\t0\t0\t0\n").rss_kb, None);
+    }
+
+    #[test]
+    fn stat_state() {
+        assert_eq!(process_state("77 (my (odd) app) Z 1 77 77 0 -1"), Some('Z'));
+        assert_eq!(process_state("1 (systemd) S 0 1 1 0 -1"), Some('S'));
+        assert_eq!(process_state("garbage"), None);
+    }
+
+    #[test]
+    fn cmdline_is_joined() {
+        assert_eq!(parse_cmdline(b"/usr/bin/node\0server.js\0--port\x008080\0"), "/usr/bin/node server.js --port 8080");
+        assert_eq!(parse_cmdline(b""), "");
+    }
+
+    #[test]
+    fn kernel_thread_flag() {
+        let kthread = "2 (kthreadd) S 0 0 0 0 -1 2129984 0 0 0 0";
+        let user = "1234 (my (odd) app) S 1 1234 1234 0 -1 4194560 120 0 0 0";
+        assert!(is_kernel_thread(kthread));
+        assert!(!is_kernel_thread(user));
+    }
+
+    #[test]
+    fn truncated_comm_uses_argv0() {
+        assert_eq!(display_name("gnome-session-b", "/usr/libexec/gnome-session-binary --autostart"), "gnome-session-binary");
+        assert_eq!(display_name("bash", "/bin/bash"), "bash");
+        assert_eq!(display_name("python3", "/usr/bin/python3 app.py"), "python3");
+    }
+
+    #[test]
+    fn passwd_lookup() {
+        let users = parse_passwd("root:x:0:0:root:/root:/bin/bash\n# comment\ndev:x:1000:1000::/home/dev:/bin/zsh\n");
+        assert_eq!(users.get(&0).map(String::as_str), Some("root"));
+        assert_eq!(users.get(&1000).map(String::as_str), Some("dev"));
+    }
+
+    #[test]
+    fn reads_fixture_tree() {
+        let root = std::env::temp_dir().join(format!("appctrl-procfs-{}", std::process::id()));
+        let proc_dir = root.join("42");
+        std::fs::create_dir_all(&proc_dir).unwrap();
+        std::fs::create_dir_all(root.join("self")).unwrap();
+        std::fs::write(proc_dir.join("status"), "Name:\tnode\nPPid:\t1\nUid:\t1000\t1000\t1000\t1000\nVmRSS:\t2048 kB\n").unwrap();
+        std::fs::write(proc_dir.join("cmdline"), b"node\0index.js\0").unwrap();
+        std::fs::write(proc_dir.join("stat"), "42 (node) S 1 42 42 0 -1 4194560 0 0").unwrap();
+
+        let processes = read_processes(&root).unwrap();
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(processes.len(), 1);
+        let p = &processes[0];
+        assert_eq!((p.pid, p.ppid, p.name.as_str(), p.rss_kb, p.uid), (42, 1, "node", Some(2048), Some(1000)));
+        assert_eq!(p.command_line, "node index.js");
+        assert!(!p.kernel_thread);
+    }
+}
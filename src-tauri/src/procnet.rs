@@ -0,0 +1,231 @@
+// Socket tables from /proc/net (Linux).
+//
+// Like procfs.rs, everything takes the procfs root so tests can use
+// fixture files.
+
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::path::Path;
+
+pub struct SocketEntry {
+    pub protocol: &'static str,
+    pub local: SocketAddr,
+    pub remote: SocketAddr,
+    /// TCP state using netstat's names ("LISTENING", "ESTABLISHED", ...),
+    /// empty for UDP.
+    pub state: &'static str,
+    pub inode: u64,
+}
+
+impl SocketEntry {
+    /// Listening TCP sockets and bound, unconnected UDP sockets.
+    pub fn is_listening(&self) -> bool {
+        match self.protocol {
+            "TCP" => self.state == "LISTENING",
+            _ => self.local.port() != 0 && self.remote.port() == 0,
+        }
+    }
+}
+
+/// Read tcp, tcp6, udp and udp6 tables under `root/net`.
+///
+/// Missing tables (e.g. IPv6 disabled) are skipped.
+pub fn read_sockets(root: &Path) -> std::io::Result<Vec<SocketEntry>> {
+    let mut sockets = Vec::new();
+    for (file, protocol) in [("tcp", "TCP"), ("tcp6", "TCP"), ("udp", "UDP"), ("udp6", "UDP")] {
+        let text = match std::fs::read_to_string(root.join("net").join(file)) {
+            Ok(t) => t,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
+        };
+        sockets.extend(text.lines().skip(1).filter_map(|l| parse_socket_line(l, protocol)));
+    }
+    Ok(sockets)
+}
+
+/// Parse one row of /proc/net/{tcp,udp}[6].
+///
+/// `  0: 0100007F:0CEA 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000  0 123456 ...`
+pub fn parse_socket_line(line: &str, protocol: &'static str) -> Option<SocketEntry> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    if fields.len() < 10 {
+        return None;
+    }
+    let local = parse_endpoint(fields[1])?;
+    let remote = parse_endpoint(fields[2])?;
+    let state = if protocol == "TCP" {
+        tcp_state_name(u8::from_str_radix(fields[3], 16).ok()?)
+    } else {
+        ""
+    };
+    let inode = fields[9].parse().ok()?;
+    Some(SocketEntry { protocol, local, remote, state, inode })
+}
+
+/// `ADDR:PORT` where ADDR is 8 (IPv4) or 32 (IPv6) hex digits made of
+/// 32-bit words in host byte order and PORT is plain hex.
+fn parse_endpoint(s: &str) -> Option<SocketAddr> {
+    let (addr, port) = s.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+    let mut bytes = Vec::with_capacity(16);
+    for i in (0..addr.len()).step_by(8) {
+        let word = u32::from_str_radix(addr.get(i..i + 8)?, 16).ok()?;
+        bytes.extend_from_slice(&word.to_ne_bytes());
+    }
+    let ip = match bytes.len() {
+        4 => IpAddr::V4(Ipv4Addr::new(bytes[0], bytes[1], bytes[2], bytes[3])),
+        16 => {
+            let mut octets = [0u8; 16];
+            octets.copy_from_slice(&bytes);
+            let v6 = Ipv6Addr::from(octets);
+            // Dual-stack sockets report IPv4 peers as ::ffff:a.b.c.d
+            match v6.to_ipv4_mapped() {
+                Some(v4) => IpAddr::V4(v4),
+                None => IpAddr::V6(v6),
+            }
+        }
+        _ => return None,
+    };
+    Some(SocketAddr::new(ip, port))
+}
+
+fn tcp_state_name(state: u8) -> &'static str {
+    // include/net/tcp_states.h
+    match state {
+        0x01 => "ESTABLISHED",
+        0x02 => "SYN_SENT",
+        0x03 => "SYN_RECEIVED",
+        0x04 => "FIN_WAIT_1",
+        0x05 => "FIN_WAIT_2",
+        0x06 => "TIME_WAIT",
+        0x07 => "CLOSED",
+        0x08 => "CLOSE_WAIT",
+        0x09 => "LAST_ACK",
+        0x0A => "LISTENING",
+        0x0B => "CLOSING",
+        0x0C => "SYN_RECEIVED",
+        _ => "UNKNOWN",
+    }
+}
+
+/// Map socket inodes to the pid holding them by scanning /proc/<pid>/fd.
+///
+/// Processes we are not allowed to inspect are skipped, so their sockets
+/// end up without an owner.
+pub fn socket_owners(root: &Path) -> HashMap<u64, u32> {
+    let mut owners = HashMap::new();
+    let entries = match std::fs::read_dir(root) {
+        Ok(e) => e,
+        Err(_) => return owners,
+    };
+    for entry in entries.flatten() {
+        let pid = match entry.file_name().to_str().and_then(|s| s.parse::<u32>().ok()) {
+            Some(pid) => pid,
+            None => continue,
+        };
+        let fds = match std::fs::read_dir(entry.path().join("fd")) {
+            Ok(fds) => fds,
+            Err(_) => continue,
+        };
+        for fd in fds.flatten() {
+            if let Ok(target) = std::fs::read_link(fd.path()) {
+                if let Some(inode) = parse_socket_link(&target.to_string_lossy()) {
+                    owners.entry(inode).or_insert(pid);
+                }
+            }
+        }
+    }
+    owners
+}
+
+/// fd links to sockets look like `socket:[12345]`.
+fn parse_socket_link(target: &str) -> Option<u64> {
+    target.strip_prefix("socket:[")?.strip_suffix(']')?.parse().ok()
+}
+
+/// Process name from /proc/<pid>/comm.
+pub fn process_name(root: &Path, pid: u32) -> Option<String> {
+    std::fs::read_to_string(root.join(pid.to_string()).join("comm"))
+        .ok()
+        .map(|s| s.trim_end().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Address fields are host-order words, so fixtures are built per host
+    fn v4_hex(ip: [u8; 4]) -> String {
+        format!("{:08X}", u32::from_ne_bytes(ip))
+    }
+
+    #[test]
+    fn parses_ipv4_listen_row() {
+        let line = format!(
+            "   0: {}:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 51234 1 0000000000000000 100 0 0 10 0",
+            v4_hex([127, 0, 0, 1])
+        );
+        let s = parse_socket_line(&line, "TCP").unwrap();
+        assert_eq!(s.local, "127.0.0.1:8080".parse().unwrap());
+        assert_eq!(s.state, "LISTENING");
+        assert_eq!(s.inode, 51234);
+        assert!(s.is_listening());
+    }
+
+    #[test]
+    fn parses_ipv6_rows() {
+        let any = "00000000000000000000000000000000";
+        let line = format!("   1: {}:0050 {}:0000 0A 00000000:00000000 00:00000000 00000000     0        0 777 1", any, any);
+        let s = parse_socket_line(&line, "TCP").unwrap();
+        assert_eq!(s.local, "[::]:80".parse().unwrap());
+
+        let loopback = format!("{:08X}{:08X}{:08X}{:08X}", 0, 0, 0, u32::from_ne_bytes([0, 0, 0, 1]));
+        let mapped = format!("{:08X}{:08X}{:08X}{}", 0, 0, u32::from_ne_bytes([0, 0, 0xff, 0xff]), v4_hex([10, 0, 0, 5]));
+        let line = format!("   2: {}:0BB8 {}:D431 01 00000000:00000000 00:00000000 00000000  1000        0 778 1", loopback, mapped);
+        let s = parse_socket_line(&line, "TCP").unwrap();
+        assert_eq!(s.local, "[::1]:3000".parse().unwrap());
+        assert_eq!(s.remote, "10.0.0.5:54321".parse().unwrap());
+        assert_eq!(s.state, "ESTABLISHED");
+        assert!(!s.is_listening());
+    }
+
+    #[test]
+    fn udp_rows() {
+        let bound = format!("  10: {}:0035 00000000:0000 07 00000000:00000000 00:00000000 00000000   101        0 900 2", v4_hex([127, 0, 0, 53]));
+        let s = parse_socket_line(&bound, "UDP").unwrap();
+        assert_eq!(s.state, "");
+        assert!(s.is_listening());
+
+        let connected = format!("  11: {}:A000 {}:0035 01 00000000:00000000 00:00000000 00000000  1000        0 901 2", v4_hex([192, 168, 1, 2]), v4_hex([1, 1, 1, 1]));
+        assert!(!parse_socket_line(&connected, "UDP").unwrap().is_listening());
+    }
+
+    #[test]
+    fn rejects_header_and_garbage() {
+        assert!(parse_socket_line("  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode", "TCP").is_none());
+        assert!(parse_socket_line("", "TCP").is_none());
+    }
+
+    #[test]
+    fn socket_links() {
+        assert_eq!(parse_socket_link("socket:[51234]"), Some(51234));
+        assert_eq!(parse_socket_link("pipe:[51234]"), None);
+        assert_eq!(parse_socket_link("/dev/null"), None);
+    }
+
+    #[test]
+    fn reads_fixture_tables() {
+        let root = std::env::temp_dir().join(format!("appctrl-procnet-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("net")).unwrap();
+        let header = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode\n";
+        let row = format!("   0: {}:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 51234 1\n", v4_hex([0, 0, 0, 0]));
+        std::fs::write(root.join("net/tcp"), format!("{}{}", header, row)).unwrap();
+        std::fs::write(root.join("net/udp"), header).unwrap();
+
+        let sockets = read_sockets(&root).unwrap();
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(sockets.len(), 1);
+        assert_eq!(sockets[0].local, "0.0.0.0:8080".parse().unwrap());
+    }
+}
@@ -0,0 +1,79 @@
+// openpty backend.
+
+use super::{Pty, PtyControl, PtySize};
+use std::fs::File;
+use std::io;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::os::unix::process::CommandExt;
+use std::process::{Command, Stdio};
+
+struct LinuxPty {
+    master: File,
+}
+
+impl PtyControl for LinuxPty {
+    fn resize(&self, size: PtySize) -> io::Result<()> {
+        let winsize = winsize(size);
+        if unsafe { libc::ioctl(self.master.as_raw_fd(), libc::TIOCSWINSZ, &winsize) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+pub fn spawn(mut cmd: Command, size: PtySize) -> io::Result<Pty> {
+    let (master, slave) = open(size)?;
+    cmd.stdin(Stdio::from(slave.try_clone()?))
+        .stdout(Stdio::from(slave.try_clone()?))
+        .stderr(Stdio::from(slave));
+    unsafe {
+        // New session with the terminal as its controlling tty, so job
+        // control and Ctrl+C reach the app
+        cmd.pre_exec(|| {
+            if libc::setsid() == -1 || libc::ioctl(0, libc::TIOCSCTTY, 0) == -1 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+    let child = cmd.spawn()?;
+    // Our copies of the slave must go, or reads never see the app hang up
+    drop(cmd);
+
+    let master = File::from(master);
+    Ok(Pty {
+        child: Box::new(child),
+        output: Box::new(master.try_clone()?),
+        input: Box::new(master.try_clone()?),
+        control: Box::new(LinuxPty { master }),
+    })
+}
+
+fn open(size: PtySize) -> io::Result<(OwnedFd, OwnedFd)> {
+    let mut master = -1;
+    let mut slave = -1;
+    let winsize = winsize(size);
+    unsafe {
+        if libc::openpty(&mut master, &mut slave, std::ptr::null_mut(), std::ptr::null(), &winsize) == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        let (master, slave) = (OwnedFd::from_raw_fd(master), OwnedFd::from_raw_fd(slave));
+        // The app must not inherit the master, and gets the slave only as
+        // its stdio
+        for fd in [&master, &slave] {
+            if libc::fcntl(fd.as_raw_fd(), libc::F_SETFD, libc::FD_CLOEXEC) == -1 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        Ok((master, slave))
+    }
+}
+
+fn winsize(size: PtySize) -> libc::winsize {
+    libc::winsize {
+        ws_row: size.rows,
+        ws_col: size.cols,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    }
+}
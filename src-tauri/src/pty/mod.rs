@@ -0,0 +1,79 @@
+// Terminal launch mode for interactive console apps.
+//
+// The app gets a pseudo-terminal (openpty on Linux, ConPTY on Windows)
+// instead of pipes, so programs that check for a TTY behave as they would
+// in a console and prompts can be answered by writing to the terminal.
+
+use std::io::{self, Read, Write};
+use std::process::{Child, Command, ExitStatus};
+
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(windows)]
+mod windows;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+pub struct PtySize {
+    pub cols: u16,
+    pub rows: u16,
+}
+
+/// Used until the log view reports its real size.
+pub const DEFAULT_SIZE: PtySize = PtySize { cols: 120, rows: 30 };
+
+/// A spawned app, whichever way it was started.
+pub trait ChildProcess: Send {
+    fn id(&self) -> u32;
+    fn wait(&mut self) -> io::Result<ExitStatus>;
+}
+
+impl ChildProcess for Child {
+    fn id(&self) -> u32 {
+        Child::id(self)
+    }
+
+    fn wait(&mut self) -> io::Result<ExitStatus> {
+        Child::wait(self)
+    }
+}
+
+pub trait PtyControl: Send + Sync {
+    fn resize(&self, size: PtySize) -> io::Result<()>;
+}
+
+/// An app attached to a pseudo-terminal.
+pub struct Pty {
+    pub child: Box<dyn ChildProcess>,
+    /// Everything the app prints; stdout and stderr share the terminal.
+    pub output: Box<dyn Read + Send>,
+    /// What the user types; Enter is "\r" as in a real terminal.
+    pub input: Box<dyn Write + Send>,
+    pub control: Box<dyn PtyControl>,
+}
+
+/// Start `cmd` on a new pseudo-terminal. Its stdio settings are replaced
+/// by the terminal.
+#[cfg(target_os = "linux")]
+pub fn spawn(cmd: Command, size: PtySize) -> io::Result<Pty> {
+    linux::spawn(with_term(cmd), size)
+}
+
+#[cfg(windows)]
+pub fn spawn(cmd: Command, size: PtySize) -> io::Result<Pty> {
+    windows::spawn(with_term(cmd), size)
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+pub fn spawn(_cmd: Command, _size: PtySize) -> io::Result<Pty> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "terminal mode is not supported on this OS"))
+}
+
+/// Programs pick their escape sequences from TERM; AppCtrl itself usually
+/// has none when started from the desktop.
+#[cfg_attr(not(any(target_os = "linux", windows)), allow(dead_code))]
+fn with_term(mut cmd: Command) -> Command {
+    if !cmd.get_envs().any(|(key, _)| key == "TERM") {
+        cmd.env("TERM", "xterm-256color");
+    }
+    cmd
+}
@@ -0,0 +1,247 @@
+// ConPTY backend.
+//
+// std::process can't hand a pseudo console to a child, so the app is
+// started with CreateProcessW using the program, arguments, environment
+// and working directory collected in the `Command`.
+
+use super::{ChildProcess, Pty, PtyControl, PtySize};
+use crate::launch;
+use std::collections::BTreeMap;
+use std::ffi::{OsStr, OsString};
+use std::fs::File;
+use std::io;
+use std::os::windows::ffi::OsStrExt;
+use std::os::windows::io::{AsRawHandle, FromRawHandle, OwnedHandle};
+use std::os::windows::process::ExitStatusExt;
+use std::process::{Command, ExitStatus};
+use std::ptr::{null, null_mut};
+use std::sync::{Arc, Mutex};
+use winapi::shared::minwindef::{DWORD, FALSE, LPVOID};
+use winapi::shared::winerror::S_OK;
+use winapi::um::consoleapi::{ClosePseudoConsole, CreatePseudoConsole, ResizePseudoConsole};
+use winapi::um::handleapi::{CloseHandle, INVALID_HANDLE_VALUE};
+use winapi::um::namedpipeapi::CreatePipe;
+use winapi::um::processthreadsapi::{
+    CreateProcessW, DeleteProcThreadAttributeList, GetExitCodeProcess, InitializeProcThreadAttributeList,
+    UpdateProcThreadAttribute, LPPROC_THREAD_ATTRIBUTE_LIST, PROCESS_INFORMATION,
+};
+use winapi::um::synchapi::WaitForSingleObject;
+use winapi::um::winbase::{
+    CREATE_NEW_PROCESS_GROUP, CREATE_UNICODE_ENVIRONMENT, EXTENDED_STARTUPINFO_PRESENT, INFINITE,
+    STARTF_USESTDHANDLES, STARTUPINFOEXW,
+};
+use winapi::um::wincontypes::{COORD, HPCON};
+use winapi::um::winnt::HANDLE;
+
+/// ProcThreadAttributeValue(22, FALSE, TRUE, FALSE); not in winapi 0.3.
+const PROC_THREAD_ATTRIBUTE_PSEUDOCONSOLE: usize = 0x0002_0016;
+
+/// Closed once the app has exited, which is what ends its output.
+struct PseudoConsole(Mutex<Option<HPCON>>);
+
+// The handle is only used under the mutex
+unsafe impl Send for PseudoConsole {}
+unsafe impl Sync for PseudoConsole {}
+
+impl PseudoConsole {
+    fn close(&self) {
+        if let Some(console) = self.0.lock().unwrap().take() {
+            unsafe { ClosePseudoConsole(console) };
+        }
+    }
+}
+
+impl Drop for PseudoConsole {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+struct ConPtyControl(Arc<PseudoConsole>);
+
+impl PtyControl for ConPtyControl {
+    fn resize(&self, size: PtySize) -> io::Result<()> {
+        let console = self.0 .0.lock().unwrap();
+        let console = console.ok_or_else(|| io::Error::new(io::ErrorKind::BrokenPipe, "terminal is closed"))?;
+        hresult(unsafe { ResizePseudoConsole(console, coord(size)) })
+    }
+}
+
+struct ConPtyChild {
+    pid: u32,
+    process: OwnedHandle,
+    console: Arc<PseudoConsole>,
+}
+
+impl ChildProcess for ConPtyChild {
+    fn id(&self) -> u32 {
+        self.pid
+    }
+
+    fn wait(&mut self) -> io::Result<ExitStatus> {
+        let process = self.process.as_raw_handle() as HANDLE;
+        let mut code: DWORD = 0;
+        let ok = unsafe {
+            WaitForSingleObject(process, INFINITE);
+            GetExitCodeProcess(process, &mut code)
+        };
+        let error = io::Error::last_os_error();
+        // The output pipe only reaches EOF once the console is gone
+        self.console.close();
+        if ok == 0 {
+            return Err(error);
+        }
+        Ok(ExitStatus::from_raw(code))
+    }
+}
+
+pub fn spawn(cmd: Command, size: PtySize) -> io::Result<Pty> {
+    let (input_read, input_write) = pipe()?;
+    let (output_read, output_write) = pipe()?;
+    let mut console: HPCON = null_mut();
+    hresult(unsafe {
+        CreatePseudoConsole(
+            coord(size),
+            input_read.as_raw_handle() as HANDLE,
+            output_write.as_raw_handle() as HANDLE,
+            0,
+            &mut console,
+        )
+    })?;
+    // The console holds its own duplicates of these
+    drop(input_read);
+    drop(output_write);
+    let console = Arc::new(PseudoConsole(Mutex::new(Some(console))));
+
+    let (pid, process) = create_process(&cmd, &console)?;
+    Ok(Pty {
+        child: Box::new(ConPtyChild { pid, process, console: console.clone() }),
+        output: Box::new(File::from(output_read)),
+        input: Box::new(File::from(input_write)),
+        control: Box::new(ConPtyControl(console)),
+    })
+}
+
+fn create_process(cmd: &Command, console: &PseudoConsole) -> io::Result<(u32, OwnedHandle)> {
+    let mut command_line = wide(OsStr::new(&launch::windows_command_line(cmd)));
+    let mut environment = environment_block(cmd);
+    let current_dir = cmd.get_current_dir().map(|dir| wide(dir.as_os_str()));
+    let console = console.0.lock().unwrap().ok_or_else(|| io::Error::other("terminal is closed"))?;
+
+    unsafe {
+        let mut list_size = 0;
+        InitializeProcThreadAttributeList(null_mut(), 1, 0, &mut list_size);
+        // u64 keeps the buffer pointer-aligned
+        let mut list_buf = vec![0u64; list_size.div_ceil(8)];
+        let list = list_buf.as_mut_ptr() as LPPROC_THREAD_ATTRIBUTE_LIST;
+        if InitializeProcThreadAttributeList(list, 1, 0, &mut list_size) == 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let result = if UpdateProcThreadAttribute(
+            list,
+            0,
+            PROC_THREAD_ATTRIBUTE_PSEUDOCONSOLE,
+            console,
+            std::mem::size_of::<HPCON>(),
+            null_mut(),
+            null_mut(),
+        ) == 0
+        {
+            Err(io::Error::last_os_error())
+        } else {
+            let mut startup: STARTUPINFOEXW = std::mem::zeroed();
+            startup.StartupInfo.cb = std::mem::size_of::<STARTUPINFOEXW>() as DWORD;
+            // Otherwise the app may pick up AppCtrl's own std handles
+            // instead of the console's
+            startup.StartupInfo.dwFlags = STARTF_USESTDHANDLES;
+            startup.StartupInfo.hStdInput = INVALID_HANDLE_VALUE;
+            startup.StartupInfo.hStdOutput = INVALID_HANDLE_VALUE;
+            startup.StartupInfo.hStdError = INVALID_HANDLE_VALUE;
+            startup.lpAttributeList = list;
+
+            let mut info: PROCESS_INFORMATION = std::mem::zeroed();
+            // CREATE_NEW_PROCESS_GROUP so stop_app can send Ctrl+Break
+            let ok = CreateProcessW(
+                null(),
+                command_line.as_mut_ptr(),
+                null_mut(),
+                null_mut(),
+                FALSE,
+                EXTENDED_STARTUPINFO_PRESENT | CREATE_UNICODE_ENVIRONMENT | CREATE_NEW_PROCESS_GROUP,
+                environment.as_mut_ptr() as LPVOID,
+                current_dir.as_ref().map_or(null(), |dir| dir.as_ptr()),
+                &mut startup.StartupInfo,
+                &mut info,
+            );
+            if ok == 0 {
+                Err(io::Error::last_os_error())
+            } else {
+                CloseHandle(info.hThread);
+                Ok((info.dwProcessId, OwnedHandle::from_raw_handle(info.hProcess as _)))
+            }
+        };
+        DeleteProcThreadAttributeList(list);
+        result
+    }
+}
+
+fn pipe() -> io::Result<(OwnedHandle, OwnedHandle)> {
+    let mut read: HANDLE = null_mut();
+    let mut write: HANDLE = null_mut();
+    unsafe {
+        if CreatePipe(&mut read, &mut write, null_mut(), 0) == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok((OwnedHandle::from_raw_handle(read as _), OwnedHandle::from_raw_handle(write as _)))
+    }
+}
+
+/// AppCtrl's environment with the `Command`'s changes applied, as the
+/// sorted, double-NUL-terminated block CreateProcessW expects.
+fn environment_block(cmd: &Command) -> Vec<u16> {
+    // Names are case-insensitive on Windows
+    let mut vars: BTreeMap<String, (OsString, OsString)> = std::env::vars_os()
+        .map(|(key, value)| (key.to_string_lossy().to_uppercase(), (key, value)))
+        .collect();
+    for (key, value) in cmd.get_envs() {
+        let name = key.to_string_lossy().to_uppercase();
+        match value {
+            Some(value) => {
+                vars.insert(name, (key.to_owned(), value.to_owned()));
+            }
+            None => {
+                vars.remove(&name);
+            }
+        }
+    }
+
+    let mut block = Vec::new();
+    for (key, value) in vars.values() {
+        block.extend(key.encode_wide());
+        block.push('=' as u16);
+        block.extend(value.encode_wide());
+        block.push(0);
+    }
+    if block.is_empty() {
+        block.push(0);
+    }
+    block.push(0);
+    block
+}
+
+fn wide(s: &OsStr) -> Vec<u16> {
+    s.encode_wide().chain(std::iter::once(0)).collect()
+}
+
+fn coord(size: PtySize) -> COORD {
+    COORD { X: size.cols as i16, Y: size.rows as i16 }
+}
+
+fn hresult(result: i32) -> io::Result<()> {
+    if result == S_OK {
+        Ok(())
+    } else {
+        Err(io::Error::from_raw_os_error(result))
+    }
+}
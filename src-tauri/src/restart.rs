@@ -0,0 +1,269 @@
+// Restart policies for managed apps.
+//
+// Kept free of Tauri types so the backoff and crash-loop rules can be
+// tested without spawning anything.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// First restart delay, doubled for every consecutive restart.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// A run this long counts as healthy and resets the backoff.
+const STABLE_RUNTIME: Duration = Duration::from_secs(30);
+/// This many exits inside the window is a crash loop.
+const CRASH_LOOP_EXITS: usize = 5;
+const CRASH_LOOP_WINDOW: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestartPolicy {
+    Never,
+    OnFailure,
+    Always,
+}
+
+impl RestartPolicy {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().replace('_', "-").as_str() {
+            "" | "never" | "no" => Some(RestartPolicy::Never),
+            "on-failure" | "onfailure" => Some(RestartPolicy::OnFailure),
+            "always" => Some(RestartPolicy::Always),
+            _ => None,
+        }
+    }
+
+    fn applies(&self, success: bool) -> bool {
+        match self {
+            RestartPolicy::Never => false,
+            RestartPolicy::OnFailure => !success,
+            RestartPolicy::Always => true,
+        }
+    }
+}
+
+/// Restart settings as sent by the frontend / stored on AppConfig.
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestartOptions {
+    pub policy: Option<String>,
+    /// Consecutive restarts allowed before giving up; `None` = no limit.
+    pub max_restarts: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GiveUpReason {
+    MaxRestarts(u32),
+    CrashLoop { exits: usize, window: Duration },
+}
+
+impl GiveUpReason {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            GiveUpReason::MaxRestarts(_) => "maxRestarts",
+            GiveUpReason::CrashLoop { .. } => "crashLoop",
+        }
+    }
+}
+
+impl fmt::Display for GiveUpReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GiveUpReason::MaxRestarts(max) => write!(f, "Restarted {} times without a stable run", max),
+            GiveUpReason::CrashLoop { exits, window } => {
+                write!(f, "Crash loop: {} exits within {}s", exits, window.as_secs())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Decision {
+    /// The policy doesn't want this exit restarted.
+    Stop,
+    Restart { attempt: u32, delay: Duration },
+    GiveUp(GiveUpReason),
+}
+
+/// Restart bookkeeping for one app, kept across its runs.
+pub struct RestartTracker {
+    policy: RestartPolicy,
+    max_restarts: Option<u32>,
+    /// Consecutive restarts since the last stable run.
+    attempts: u32,
+    recent_exits: VecDeque<Instant>,
+    /// A restart was scheduled and hasn't been claimed or cancelled yet.
+    pending: bool,
+}
+
+impl RestartTracker {
+    /// Unknown policy names fall back to `Never`.
+    pub fn new(options: &RestartOptions) -> Self {
+        let policy = options
+            .policy
+            .as_deref()
+            .and_then(RestartPolicy::parse)
+            .unwrap_or(RestartPolicy::Never);
+        Self {
+            policy,
+            max_restarts: options.max_restarts,
+            attempts: 0,
+            recent_exits: VecDeque::new(),
+            pending: false,
+        }
+    }
+
+    pub fn max_restarts(&self) -> Option<u32> {
+        self.max_restarts
+    }
+
+    /// Decide what to do after the app exited on its own after `runtime`.
+    pub fn on_exit(&mut self, success: bool, runtime: Duration, now: Instant) -> Decision {
+        if !self.policy.applies(success) {
+            return Decision::Stop;
+        }
+        self.schedule(runtime, now)
+    }
+
+    /// Decide what to do after the app was stopped to be restarted, by a
+    /// log trigger or failed liveness checks, after going well for
+    /// `runtime`. The policy doesn't apply, but the backoff and the
+    /// give-up rules do, so an app that keeps asking for a restart doesn't
+    /// restart forever.
+    pub fn on_forced_restart(&mut self, runtime: Duration, now: Instant) -> Decision {
+        self.schedule(runtime, now)
+    }
+
+    fn schedule(&mut self, runtime: Duration, now: Instant) -> Decision {
+        if runtime >= STABLE_RUNTIME {
+            self.attempts = 0;
+        }
+
+        self.recent_exits.push_back(now);
+        while let Some(&first) = self.recent_exits.front() {
+            if now.duration_since(first) > CRASH_LOOP_WINDOW {
+                self.recent_exits.pop_front();
+            } else {
+                break;
+            }
+        }
+        if self.recent_exits.len() >= CRASH_LOOP_EXITS {
+            return Decision::GiveUp(GiveUpReason::CrashLoop {
+                exits: self.recent_exits.len(),
+                window: CRASH_LOOP_WINDOW,
+            });
+        }
+        if let Some(max) = self.max_restarts {
+            if self.attempts >= max {
+                return Decision::GiveUp(GiveUpReason::MaxRestarts(max));
+            }
+        }
+
+        self.attempts += 1;
+        self.pending = true;
+        Decision::Restart {
+            attempt: self.attempts,
+            delay: backoff(self.attempts),
+        }
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.pending
+    }
+
+    /// Claim the scheduled restart; false if it was already claimed.
+    pub fn take_pending(&mut self) -> bool {
+        std::mem::take(&mut self.pending)
+    }
+}
+
+/// Delay before restart number `attempt` (1-based).
+fn backoff(attempt: u32) -> Duration {
+    let factor = 1u32.checked_shl(attempt.saturating_sub(1)).unwrap_or(u32::MAX);
+    INITIAL_BACKOFF.saturating_mul(factor).min(MAX_BACKOFF)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker(policy: &str, max_restarts: Option<u32>) -> RestartTracker {
+        RestartTracker::new(&RestartOptions {
+            policy: Some(policy.to_string()),
+            max_restarts,
+        })
+    }
+
+    const QUICK: Duration = Duration::from_secs(2);
+
+    #[test]
+    fn policies() {
+        let now = Instant::now();
+        assert_eq!(tracker("never", None).on_exit(false, QUICK, now), Decision::Stop);
+        assert_eq!(tracker("on-failure", None).on_exit(true, QUICK, now), Decision::Stop);
+        assert!(matches!(tracker("on-failure", None).on_exit(false, QUICK, now), Decision::Restart { .. }));
+        assert!(matches!(tracker("always", None).on_exit(true, QUICK, now), Decision::Restart { .. }));
+        assert_eq!(tracker("bogus", None).on_exit(false, QUICK, now), Decision::Stop);
+        assert_eq!(RestartPolicy::parse("On_Failure"), Some(RestartPolicy::OnFailure));
+    }
+
+    #[test]
+    fn backoff_doubles_and_caps() {
+        assert_eq!(backoff(1), Duration::from_secs(1));
+        assert_eq!(backoff(2), Duration::from_secs(2));
+        assert_eq!(backoff(4), Duration::from_secs(8));
+        assert_eq!(backoff(7), MAX_BACKOFF);
+        assert_eq!(backoff(40), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn max_restarts_then_give_up() {
+        let mut t = tracker("always", Some(2));
+        let start = Instant::now();
+        // Spread exits out so the crash loop detector stays quiet
+        let at = |step: u64| start + Duration::from_secs(step * 120);
+        assert_eq!(t.on_exit(false, QUICK, at(0)), Decision::Restart { attempt: 1, delay: backoff(1) });
+        assert_eq!(t.on_exit(false, QUICK, at(1)), Decision::Restart { attempt: 2, delay: backoff(2) });
+        assert_eq!(t.on_exit(false, QUICK, at(2)), Decision::GiveUp(GiveUpReason::MaxRestarts(2)));
+    }
+
+    #[test]
+    fn stable_run_resets_backoff() {
+        let mut t = tracker("always", Some(2));
+        let start = Instant::now();
+        t.on_exit(false, QUICK, start);
+        t.on_exit(false, QUICK, start + Duration::from_secs(120));
+        let decision = t.on_exit(false, Duration::from_secs(300), start + Duration::from_secs(600));
+        assert_eq!(decision, Decision::Restart { attempt: 1, delay: backoff(1) });
+    }
+
+    #[test]
+    fn crash_loop_detected() {
+        let mut t = tracker("on-failure", None);
+        let start = Instant::now();
+        let mut decision = Decision::Stop;
+        for i in 0..CRASH_LOOP_EXITS as u64 {
+            decision = t.on_exit(false, QUICK, start + Duration::from_secs(i * 5));
+        }
+        assert_eq!(decision, Decision::GiveUp(GiveUpReason::CrashLoop { exits: 5, window: CRASH_LOOP_WINDOW }));
+    }
+
+    #[test]
+    fn forced_restarts_ignore_policy_but_give_up() {
+        let mut t = tracker("never", Some(1));
+        let start = Instant::now();
+        assert_eq!(t.on_forced_restart(QUICK, start), Decision::Restart { attempt: 1, delay: backoff(1) });
+        let decision = t.on_forced_restart(QUICK, start + Duration::from_secs(120));
+        assert_eq!(decision, Decision::GiveUp(GiveUpReason::MaxRestarts(1)));
+    }
+
+    #[test]
+    fn pending_restart_is_claimed_once() {
+        let mut t = tracker("always", None);
+        assert!(!t.is_pending());
+        t.on_exit(true, QUICK, Instant::now());
+        assert!(t.is_pending());
+        assert!(t.take_pending());
+        assert!(!t.take_pending());
+    }
+}
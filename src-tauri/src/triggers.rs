@@ -0,0 +1,146 @@
+// Log triggers: regex rules checked against every line an app prints.
+//
+// A match is reported to the frontend and can restart or stop the app or
+// start another one. Kept free of Tauri types, like restart.rs, so the
+// matching rules can be tested on their own.
+
+use regex::Regex;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TriggerAction {
+    /// Only report the match.
+    #[default]
+    None,
+    Restart,
+    Stop,
+    /// Start the app named by `target_app_id`.
+    StartApp,
+}
+
+/// One rule as sent by the frontend / stored on AppConfig.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TriggerRule {
+    pub pattern: String,
+    #[serde(default)]
+    pub action: TriggerAction,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_app_id: Option<String>,
+    /// Whether a match counts towards the app's error count; off for
+    /// rules like "Listening on port (\d+)".
+    #[serde(default = "default_is_error")]
+    pub is_error: bool,
+    /// Keys this version doesn't know, kept as they are.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+fn default_is_error() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TriggerMatch {
+    pub pattern: String,
+    /// The capture groups, `None` for a group that didn't take part.
+    pub captures: Vec<Option<String>>,
+    pub is_error: bool,
+    /// What to do now; `None` once the rule's action has fired this run.
+    pub action: TriggerAction,
+    pub target_app_id: Option<String>,
+}
+
+/// The compiled rules for one run of an app.
+#[derive(Debug)]
+pub struct Triggers {
+    rules: Vec<(Regex, TriggerRule, bool)>,
+}
+
+impl Triggers {
+    /// Rules with an empty pattern are skipped.
+    pub fn compile(rules: &[TriggerRule]) -> Result<Self, String> {
+        let mut compiled = Vec::new();
+        for rule in rules.iter().filter(|rule| !rule.pattern.trim().is_empty()) {
+            let regex = Regex::new(&rule.pattern).map_err(|e| format!("Invalid pattern {:?}: {}", rule.pattern, e))?;
+            if rule.action == TriggerAction::StartApp && rule.target_app_id.is_none() {
+                return Err(format!("Rule {:?} has no app to start", rule.pattern));
+            }
+            compiled.push((regex, rule.clone(), false));
+        }
+        Ok(Self { rules: compiled })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Every rule matching `line`. Each rule's action fires once per run,
+    /// so a burst of errors doesn't restart the app over and over; later
+    /// matches are still reported.
+    pub fn check(&mut self, line: &str) -> Vec<TriggerMatch> {
+        let mut matches = Vec::new();
+        for (regex, rule, fired) in &mut self.rules {
+            let Some(caps) = regex.captures(line) else { continue };
+            let action = if *fired { TriggerAction::None } else { rule.action };
+            *fired |= action != TriggerAction::None;
+            matches.push(TriggerMatch {
+                pattern: rule.pattern.clone(),
+                captures: caps.iter().skip(1).map(|m| m.map(|m| m.as_str().to_string())).collect(),
+                is_error: rule.is_error,
+                action,
+                target_app_id: rule.target_app_id.clone(),
+            });
+        }
+        matches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(pattern: &str, action: TriggerAction) -> TriggerRule {
+        TriggerRule { pattern: pattern.to_string(), action, target_app_id: None, is_error: true, extra: Default::default() }
+    }
+
+    #[test]
+    fn captures_groups() {
+        let mut triggers = Triggers::compile(&[rule(r"Listening on port (\d+)", TriggerAction::None)]).unwrap();
+        assert!(triggers.check("starting up").is_empty());
+        let matches = triggers.check("Listening on port 8080");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].captures, [Some("8080".to_string())]);
+    }
+
+    #[test]
+    fn actions_fire_once_per_run() {
+        let mut triggers = Triggers::compile(&[
+            rule("ERROR|panic", TriggerAction::Restart),
+            rule("panic", TriggerAction::None),
+        ])
+        .unwrap();
+        let first = triggers.check("thread 'main' panicked");
+        assert_eq!(first.iter().map(|m| m.action).collect::<Vec<_>>(), [TriggerAction::Restart, TriggerAction::None]);
+        let second = triggers.check("ERROR again");
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0].action, TriggerAction::None);
+    }
+
+    #[test]
+    fn invalid_rules_are_rejected() {
+        assert!(Triggers::compile(&[rule("(unclosed", TriggerAction::None)]).is_err());
+        assert!(Triggers::compile(&[rule("x", TriggerAction::StartApp)]).is_err());
+        assert!(Triggers::compile(&[rule("  ", TriggerAction::Stop)]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn rules_deserialize_with_defaults() {
+        let rule: TriggerRule = serde_json::from_str(r#"{"pattern":"Traceback","action":"startApp","targetAppId":"a1"}"#).unwrap();
+        assert_eq!(rule.action, TriggerAction::StartApp);
+        assert_eq!(rule.target_app_id.as_deref(), Some("a1"));
+        assert!(rule.is_error);
+        let rule: TriggerRule = serde_json::from_str(r#"{"pattern":"x"}"#).unwrap();
+        assert_eq!(rule.action, TriggerAction::None);
+    }
+}
@@ -1,11 +1,11 @@
 import type { Component } from 'solid-js';
-import { Show, For, createSignal, onCleanup, createEffect, onMount } from 'solid-js';
+import { Show, For, Index, createSignal, onCleanup, createEffect, onMount } from 'solid-js';
 import { invoke } from '@tauri-apps/api/core';
 import { open as openDialog } from '@tauri-apps/plugin-dialog';
 import { openUrl } from '@tauri-apps/plugin-opener';
 import { listen, type UnlistenFn } from '@tauri-apps/api/event';
 import { AppProvider, useApp } from './store/appStore';
-import type { AppType, AppConfig, PortInfo, TaskInfo } from './types';
+import type { AppType, AppConfig, PortInfo, ConnectionInfo, TaskInfo, RestartPolicy, AppStoppedEvent, AppOutputEvent, AppTriggerEvent, TriggerRule, TriggerAction, AppHealthEvent, HealthProbe, HealthStatus, ConfigError, LogRecord, LogStream, LogSpan, ProcessError } from './types';
 import './index.css';
 import { FileExplorerModal } from './FileExplorerModal';
 
@@ -18,6 +18,12 @@ const App: Component = () => {
   );
 };
 
+// For changes made outside a form, e.g. a delete or the theme toggle
+const showSaveError = async (error: ConfigError) => {
+  const { message } = await import('@tauri-apps/plugin-dialog');
+  await message(`Không lưu được cấu hình: ${error.message}`, { title: 'Lỗi cấu hình', kind: 'error' });
+};
+
 // Main Window
 const MainWindow: Component = () => {
   const [store, actions] = useApp();
@@ -25,7 +31,11 @@ const MainWindow: Component = () => {
 
   // Check status of all apps on load
   const checkAllAppsStatus = async () => {
+    actions.refreshGroupStatus();
     for (const app of store.apps) {
+      invoke<HealthStatus>('get_app_health', { appId: app.id })
+        .then((status) => actions.setHealth(app.id, status))
+        .catch(console.error);
       if (app.appType === 'exe' || app.executablePath.toLowerCase().endsWith('.exe')) {
         try {
           const isRunning = await invoke<boolean>('check_process_running', { exePath: app.executablePath });
@@ -41,10 +51,28 @@ const MainWindow: Component = () => {
 
   // Listen for app-stopped event
   let unlistenStopped: UnlistenFn | undefined;
+  let unlistenStarted: UnlistenFn | undefined;
+  let unlistenTrigger: UnlistenFn | undefined;
+  let unlistenHealth: UnlistenFn | undefined;
   const setupStoppedListener = async () => {
-    unlistenStopped = await listen<{ appId: string }>('app-stopped', (event) => {
+    unlistenStopped = await listen<AppStoppedEvent>('app-stopped', (event) => {
       actions.setAppRunning(event.payload.appId, false);
     });
+    // Starts from the tray or a log trigger
+    unlistenStarted = await listen<{ appId: string }>('app-started', (event) => {
+      actions.setAppRunning(event.payload.appId, true);
+    });
+    unlistenTrigger = await listen<AppTriggerEvent>('app-trigger', (event) => {
+      actions.setErrorCount(event.payload.appId, event.payload.errorCount);
+    });
+    unlistenHealth = await listen<AppHealthEvent>('app-health', (event) => {
+      const { appId, ...status } = event.payload;
+      actions.setHealth(appId, status);
+      actions.refreshGroupStatus();
+    });
+    invoke<Record<string, number>>('get_app_error_counts')
+      .then((counts) => Object.entries(counts).forEach(([appId, count]) => actions.setErrorCount(appId, count)))
+      .catch(console.error);
 
     // Initial check
     checkAllAppsStatus();
@@ -54,7 +82,12 @@ const MainWindow: Component = () => {
     onCleanup(() => clearInterval(interval));
   };
   setupStoppedListener();
-  onCleanup(() => { if (unlistenStopped) unlistenStopped(); });
+  onCleanup(() => {
+    if (unlistenStopped) unlistenStopped();
+    if (unlistenStarted) unlistenStarted();
+    if (unlistenTrigger) unlistenTrigger();
+    if (unlistenHealth) unlistenHealth();
+  });
 
   // Sync tray setting with backend whenever it changes
   createEffect(() => {
@@ -62,9 +95,10 @@ const MainWindow: Component = () => {
     invoke('set_minimize_to_tray', { minimize }).catch(console.error);
   });
 
-  const toggleTheme = () => {
+  const toggleTheme = async () => {
     const newTheme = store.settings.theme === 'dark' ? 'light' : 'dark';
-    actions.updateSettings({ theme: newTheme });
+    const error = await actions.updateSettings({ theme: newTheme });
+    if (error) await showSaveError(error);
   };
 
   return (
@@ -150,6 +184,7 @@ const MainWindow: Component = () => {
             </div>
           }
         >
+          <GroupBar />
           <div class="grid grid-cols-3 sm:grid-cols-4 gap-3">
             <For each={store.apps}>
               {(app) => (
@@ -173,6 +208,9 @@ const MainWindow: Component = () => {
       <Show when={store.modal.type === 'add' || store.modal.type === 'edit'}>
         <AppModal />
       </Show>
+      <Show when={store.modal.type === 'group'}>
+        <GroupModal />
+      </Show>
       <Show when={store.modal.type === 'settings'}>
         <SettingsModal />
       </Show>
@@ -189,6 +227,70 @@ const MainWindow: Component = () => {
   );
 };
 
+// Group Bar
+// Group chips above the app grid: aggregate status and start/stop all
+const GroupBar: Component = () => {
+  const [store, actions] = useApp();
+  const isDark = () => store.settings.theme === 'dark';
+  const [busy, setBusy] = createSignal<string | null>(null);
+
+  const statusColor = (groupId: string) => {
+    switch (store.groupStatus[groupId]?.state) {
+      case 'ready': return 'bg-green-400';
+      case 'starting': return 'bg-yellow-400';
+      case 'partial': return 'bg-orange-400';
+      case 'unhealthy': return 'bg-red-500';
+      default: return isDark() ? 'bg-white/20' : 'bg-black/20';
+    }
+  };
+
+  const run = async (command: 'start_group' | 'stop_group', groupId: string) => {
+    setBusy(groupId);
+    try {
+      await invoke(command, { groupId });
+    } catch (err) {
+      const { message } = await import('@tauri-apps/plugin-dialog');
+      await message(String(err), { title: 'Nhóm ứng dụng', kind: 'error' });
+    } finally {
+      setBusy(null);
+      actions.refreshGroupStatus();
+    }
+  };
+
+  const chipClass = () => isDark() ? 'bg-white/5 border-white/10' : 'bg-white/60 border-black/5';
+  const btnClass = () => isDark() ? 'hover:bg-white/10' : 'hover:bg-black/10';
+
+  return (
+    <div class="flex flex-wrap items-center gap-2 mb-3">
+      <For each={store.groups}>
+        {(group) => {
+          const status = () => store.groupStatus[group.id];
+          return (
+            <div class={`flex items-center gap-2 pl-3 pr-1 py-1 rounded-lg border text-sm ${chipClass()}`}>
+              <span class={`w-2 h-2 rounded-full ${statusColor(group.id)}`} />
+              <span class="font-medium">{group.name}</span>
+              <span class={`text-xs ${isDark() ? 'text-white/40' : 'text-black/40'}`}>
+                {status()?.running ?? 0}/{group.apps.length}
+              </span>
+              <button onClick={() => run('start_group', group.id)} disabled={busy() === group.id}
+                class={`w-7 h-7 rounded flex items-center justify-center disabled:opacity-40 ${btnClass()}`} title="Chạy tất cả">▶</button>
+              <button onClick={() => run('stop_group', group.id)} disabled={busy() === group.id}
+                class={`w-7 h-7 rounded flex items-center justify-center disabled:opacity-40 ${btnClass()}`} title="Dừng tất cả">■</button>
+              <button onClick={() => actions.openGroupModal(group.id)}
+                class={`w-7 h-7 rounded flex items-center justify-center ${btnClass()}`} title="Sửa nhóm">✏️</button>
+            </div>
+          );
+        }}
+      </For>
+      <button onClick={() => actions.openGroupModal()}
+        class={`px-3 py-1.5 rounded-lg border border-dashed text-sm transition-colors
+          ${isDark() ? 'border-white/20 text-white/50 hover:text-white/80' : 'border-black/20 text-black/50 hover:text-black/80'}`}>
+        + Nhóm
+      </button>
+    </div>
+  );
+};
+
 // App Card
 const AppCard: Component<{
   app: AppConfig;
@@ -197,13 +299,23 @@ const AppCard: Component<{
 }> = (props) => {
   const [store, actions] = useApp();
   const [isHovered, setIsHovered] = createSignal(false);
+  const health = () => store.health[props.app.id];
+  const healthColor = () => {
+    switch (health()?.state) {
+      case 'starting': return 'bg-yellow-400';
+      case 'unhealthy': return 'bg-red-500';
+      default: return 'bg-green-400';
+    }
+  };
 
   const handleRun = async (e: Event) => {
     e.stopPropagation();
     if (props.app.isRunning) {
       await invoke('stop_app', {
         appId: props.app.id,
-        exePath: props.app.executablePath
+        exePath: props.app.executablePath,
+        stopSignal: props.app.stopSignal || null,
+        stopTimeout: props.app.stopTimeout ?? null,
       });
       actions.setAppRunning(props.app.id, false);
     } else {
@@ -219,6 +331,17 @@ const AppCard: Component<{
           workingDir: props.app.workingDirectory || '',
           args: props.app.arguments || '',
           envVars: props.app.environmentVars || '',
+          shell: props.app.shell || null,
+          restart: {
+            policy: props.app.restartPolicy || 'never',
+            maxRestarts: props.app.maxRestarts ?? null,
+          },
+          encoding: props.app.encoding || null,
+          forceColor: props.app.forceColor ?? false,
+          terminal: props.app.terminal ?? false,
+          keepStdin: props.app.keepStdin ?? false,
+          triggers: props.app.triggers ?? [],
+          health: props.app.health ?? null,
         });
       } catch (err) {
         actions.appendLog(props.app.id, `❌ Error: ${err}`);
@@ -240,7 +363,8 @@ const AppCard: Component<{
       kind: 'warning',
     });
     if (confirmed) {
-      actions.deleteApp(props.app.id);
+      const error = await actions.deleteApp(props.app.id);
+      if (error) await showSaveError(error);
     }
   };
 
@@ -280,9 +404,18 @@ const AppCard: Component<{
       {/* Name */}
       <p class={`text-center text-xs font-medium truncate ${store.settings.theme === 'dark' ? 'text-white' : 'text-gray-800'}`}>{props.app.name}</p>
 
-      {/* Running indicator */}
+      {/* Running indicator, colored by health */}
       <Show when={props.app.isRunning}>
-        <div class="absolute top-1.5 right-1.5 w-2 h-2 rounded-full bg-green-400 animate-pulse" />
+        <div class={`absolute top-1.5 right-1.5 w-2 h-2 rounded-full animate-pulse ${healthColor()}`}
+          title={health()?.reason ?? health()?.state ?? ''} />
+      </Show>
+
+      {/* Error trigger matches */}
+      <Show when={(store.errorCounts[props.app.id] ?? 0) > 0}>
+        <div class="absolute top-1 left-1.5 px-1.5 rounded-full bg-red-500 text-white text-[10px] font-medium"
+          title="Lines matched by error triggers">
+          {store.errorCounts[props.app.id]}
+        </div>
       </Show>
 
       {/* Hover Controls */}
@@ -319,6 +452,25 @@ const AppCard: Component<{
   );
 };
 
+// Terminal palette tuned for the dark log panel
+const ANSI_COLORS: Record<string, string> = {
+  black: '#3b3b3b', red: '#f87171', green: '#4ade80', yellow: '#facc15',
+  blue: '#60a5fa', magenta: '#e879f9', cyan: '#22d3ee', white: '#e5e5e5',
+  brightBlack: '#737373', brightRed: '#fca5a5', brightGreen: '#86efac', brightYellow: '#fde047',
+  brightBlue: '#93c5fd', brightMagenta: '#f0abfc', brightCyan: '#67e8f9', brightWhite: '#ffffff',
+};
+
+function spanStyle(span: LogSpan): Record<string, string> {
+  const style: Record<string, string> = {};
+  if (span.fg) style.color = ANSI_COLORS[span.fg] || span.fg;
+  if (span.bg) style['background-color'] = ANSI_COLORS[span.bg] || span.bg;
+  if (span.bold) style['font-weight'] = 'bold';
+  if (span.dim) style.opacity = '0.6';
+  if (span.italic) style['font-style'] = 'italic';
+  if (span.underline) style['text-decoration'] = 'underline';
+  return style;
+}
+
 // Parse text and make URLs clickable
 function parseLogLine(text: string): { type: 'text' | 'link'; content: string }[] {
   const urlRegex = /(https?:\/\/[^\s]+)/g;
@@ -346,22 +498,60 @@ const LogPanel: Component<{ appId: string; onClose: () => void }> = (props) => {
   const [store, actions] = useApp();
   let logRef: HTMLDivElement | undefined;
   let unlisten: UnlistenFn | undefined;
+  // Live events held back while a backfill is in flight
+  let pending: AppOutputEvent[] | null = null;
+
+  const scrollToBottom = () => {
+    setTimeout(() => {
+      if (logRef) logRef.scrollTop = logRef.scrollHeight;
+    }, 10);
+  };
 
   const setupListener = async () => {
-    unlisten = await listen<{ appId: string; line: string }>('app-output', (event) => {
-      if (event.payload.appId === props.appId) {
-        actions.appendLog(props.appId, event.payload.line);
-        setTimeout(() => {
-          if (logRef) logRef.scrollTop = logRef.scrollHeight;
-        }, 10);
+    unlisten = await listen<AppOutputEvent>('app-output', (event) => {
+      if (event.payload.appId !== props.appId) return;
+      if (pending) {
+        pending.push(event.payload);
+      } else {
+        actions.appendLog(props.appId, event.payload);
+        scrollToBottom();
       }
     });
   };
 
-  setupListener();
+  // Fetch what was printed while the panel was closed, then replay the
+  // events that arrived meanwhile; appendLog drops anything seen twice
+  const backfill = async (appId: string) => {
+    const held: AppOutputEvent[] = [];
+    pending = held;
+    try {
+      const backlog = await invoke<LogRecord[]>('get_app_logs', {
+        appId,
+        sinceSeq: store.logSeq[appId] ?? 0,
+      });
+      backlog.forEach((entry) => actions.appendLog(appId, entry));
+    } catch (e) {
+      console.error('Failed to load recent output:', e);
+    }
+    if (pending === held) pending = null;
+    held.forEach((event) => actions.appendLog(event.appId, event));
+    scrollToBottom();
+  };
+
+  // The listener has to be up before the backfill so nothing falls in between
+  const listening = setupListener();
+  createEffect(() => {
+    const appId = props.appId;
+    listening.then(() => backfill(appId));
+  });
   onCleanup(() => { if (unlisten) unlisten(); });
 
-  const logs = () => store.logs[props.appId] || [];
+  const [streamFilter, setStreamFilter] = createSignal<LogStream | 'all'>('all');
+  const logs = () => {
+    const entries = store.logs[props.appId] || [];
+    const stream = streamFilter();
+    return stream === 'all' ? entries : entries.filter((entry) => entry.stream === stream);
+  };
   const app = () => store.apps.find(a => a.id === props.appId);
 
   const handleLinkClick = async (url: string) => {
@@ -373,10 +563,49 @@ const LogPanel: Component<{ appId: string; onClose: () => void }> = (props) => {
   };
 
   const copyLogs = () => {
-    const text = logs().join('\n');
+    const text = logs().map((entry) => entry.text).join('\n');
     navigator.clipboard.writeText(text);
   };
 
+  // What is typed here goes to the app's terminal, which echoes it back
+  // into the log, or as a line to its stdin pipe
+  const [input, setInput] = createSignal('');
+  const interactive = () => !!((app()?.terminal || app()?.keepStdin) && app()?.isRunning);
+
+  const sendInput = async (command: string, args: Record<string, string>) => {
+    try {
+      await invoke(command, { appId: props.appId, ...args });
+    } catch (e) {
+      actions.appendLog(props.appId, `❌ ${e}`);
+    }
+  };
+
+  const submitInput = () => {
+    const text = input();
+    if (app()?.terminal) {
+      sendInput('write_app_stdin', { data: text + '\r' });
+    } else {
+      actions.appendLog(props.appId, `> ${text}`);
+      sendInput('send_app_input', { text });
+    }
+    setInput('');
+  };
+
+  // Keep the terminal as wide as the log view so apps wrap where it does
+  const CHAR_WIDTH = 7.2;
+  const LINE_HEIGHT = 19.5;
+  onMount(() => {
+    if (!logRef) return;
+    const observer = new ResizeObserver(() => {
+      if (!logRef || !app()?.terminal) return;
+      const cols = Math.max(20, Math.floor((logRef.clientWidth - 24) / CHAR_WIDTH));
+      const rows = Math.max(5, Math.floor((logRef.clientHeight - 24) / LINE_HEIGHT));
+      invoke('resize_app_terminal', { appId: props.appId, cols, rows }).catch(() => {});
+    });
+    observer.observe(logRef);
+    onCleanup(() => observer.disconnect());
+  });
+
   return (
     <div class="h-48 bg-black/60 border-t border-white/10 flex flex-col flex-shrink-0">
       <div class="flex items-center justify-between px-3 py-2 bg-black/40 border-b border-white/10">
@@ -387,6 +616,23 @@ const LogPanel: Component<{ appId: string; onClose: () => void }> = (props) => {
           <span class="text-xs font-medium text-white/70">{app()?.name || 'Log'}</span>
         </div>
         <div class="flex gap-1">
+          <Show when={(store.errorCounts[props.appId] ?? 0) > 0}>
+            <button onClick={() => actions.resetErrorCount(props.appId)}
+              class="text-xs px-2 py-1 rounded bg-red-500/20 hover:bg-red-500/30 text-red-300" title="Error trigger matches, click to reset">
+              ⚠ {store.errorCounts[props.appId]}
+            </button>
+          </Show>
+          <select
+            value={streamFilter()}
+            onChange={(e) => setStreamFilter(e.currentTarget.value as LogStream | 'all')}
+            class="text-xs px-1 py-1 rounded bg-white/5 hover:bg-white/10 outline-none"
+            title="Filter by stream"
+          >
+            <option value="all">All</option>
+            <option value="stdout">stdout</option>
+            <option value="stderr">stderr</option>
+            <option value="system">System</option>
+          </select>
           <button onClick={copyLogs} class="text-xs px-2 py-1 rounded bg-white/5 hover:bg-white/10" title="Copy all">📋</button>
           <button onClick={() => actions.clearLogs(props.appId)} class="text-xs px-2 py-1 rounded bg-white/5 hover:bg-white/10">Clear</button>
           <button onClick={props.onClose} class="text-xs px-2 py-1 rounded bg-white/5 hover:bg-white/10">✕</button>
@@ -398,27 +644,37 @@ const LogPanel: Component<{ appId: string; onClose: () => void }> = (props) => {
       >
         <Show when={logs().length > 0} fallback={<p class="text-white/30 italic">Waiting for output...</p>}>
           <For each={logs()}>
-            {(line) => {
-              const isError = line.startsWith('[stderr]') || line.includes('❌') || line.includes('error') || line.includes('Error');
-              const isSuccess = line.startsWith('✓');
-              const isWarning = line.startsWith('⚠');
+            {({ stream, timestamp, text, spans }) => {
+              const isSystem = stream === 'system';
+              const isError = stream === 'stderr' || text.includes('❌') || text.includes('error') || text.includes('Error');
+              const isSuccess = isSystem && text.startsWith('✓');
+              const isWarning = isSystem && text.startsWith('⚠');
 
               return (
-                <div class={`py-0.5 ${isError ? 'text-red-400' : isSuccess ? 'text-green-400' : isWarning ? 'text-yellow-400' : 'text-white/80'}`}>
-                  <For each={parseLogLine(line)}>
-                    {(part) => (
-                      <Show
-                        when={part.type === 'link'}
-                        fallback={<span>{part.content}</span>}
-                      >
-                        <a
-                          href="#"
-                          onClick={(e) => { e.preventDefault(); handleLinkClick(part.content); }}
-                          class="text-blue-400 hover:text-blue-300 underline cursor-pointer"
-                        >
-                          {part.content}
-                        </a>
-                      </Show>
+                <div
+                  class={`py-0.5 ${isError ? 'text-red-400' : isSuccess ? 'text-green-400' : isWarning ? 'text-yellow-400' : isSystem ? 'text-white/50' : 'text-white/80'}`}
+                  title={new Date(timestamp).toLocaleString()}
+                >
+                  <For each={spans ?? [{ text }]}>
+                    {(span) => (
+                      <span style={spanStyle(span)}>
+                        <For each={parseLogLine(span.text)}>
+                          {(part) => (
+                            <Show
+                              when={part.type === 'link'}
+                              fallback={<span>{part.content}</span>}
+                            >
+                              <a
+                                href="#"
+                                onClick={(e) => { e.preventDefault(); handleLinkClick(part.content); }}
+                                class="text-blue-400 hover:text-blue-300 underline cursor-pointer"
+                              >
+                                {part.content}
+                              </a>
+                            </Show>
+                          )}
+                        </For>
+                      </span>
                     )}
                   </For>
                 </div>
@@ -427,11 +683,90 @@ const LogPanel: Component<{ appId: string; onClose: () => void }> = (props) => {
           </For>
         </Show>
       </div>
+      <Show when={interactive()}>
+        <div class="flex items-center gap-1 px-3 py-1 border-t border-white/10 bg-black/40">
+          <span class="text-xs font-mono text-white/40">&gt;</span>
+          <input
+            type="text"
+            value={input()}
+            onInput={(e) => setInput(e.currentTarget.value)}
+            onKeyDown={(e) => { if (e.key === 'Enter') { e.preventDefault(); submitInput(); } }}
+            placeholder="Type input and press Enter"
+            class="flex-1 bg-transparent outline-none text-xs font-mono text-white/80"
+          />
+          <Show when={app()?.terminal}>
+            <button onClick={() => sendInput('write_app_stdin', { data: '\u0003' })} class="text-xs px-2 py-0.5 rounded bg-white/5 hover:bg-white/10" title="Send Ctrl+C">^C</button>
+          </Show>
+        </div>
+      </Show>
+    </div>
+  );
+};
+
+// Probes without a port or pattern are dropped on save
+const validProbe = (probe?: HealthProbe): HealthProbe | undefined => {
+  if (!probe) return undefined;
+  if (probe.kind === 'log') return probe.pattern.trim() ? probe : undefined;
+  return probe.port > 0 && probe.port < 65536 ? probe : undefined;
+};
+
+// Health probe fields in the app modal
+const ProbeEditor: Component<{
+  label: string;
+  probe?: HealthProbe;
+  onChange: (probe?: HealthProbe) => void;
+  inputClass: string;
+  labelClass: string;
+}> = (props) => {
+  const port = () => (props.probe && props.probe.kind !== 'log' ? props.probe.port || '' : '');
+  const setKind = (kind: string) => {
+    const current = port() || 0;
+    if (kind === 'tcp') props.onChange({ kind: 'tcp', port: current });
+    else if (kind === 'http') props.onChange({ kind: 'http', port: current, path: '/' });
+    else if (kind === 'log') props.onChange({ kind: 'log', pattern: '' });
+    else props.onChange(undefined);
+  };
+  const update = (data: Partial<{ port: number; path: string; pattern: string }>) =>
+    props.onChange({ ...props.probe, ...data } as HealthProbe);
+  const fieldClass = () => `px-3 py-2 rounded-lg border outline-none text-sm transition-colors ${props.inputClass}`;
+
+  return (
+    <div>
+      <label class={`text-xs mb-1 block ${props.labelClass}`}>{props.label}</label>
+      <div class="flex gap-2">
+        <select value={props.probe?.kind ?? ''} onChange={e => setKind(e.currentTarget.value)} class={`w-28 ${fieldClass()}`}>
+          <option value="">Không</option>
+          <option value="tcp">Cổng TCP</option>
+          <option value="http">HTTP 2xx</option>
+          <option value="log">Dòng log</option>
+        </select>
+        <Show when={props.probe && props.probe.kind !== 'log'}>
+          <input type="number" min="1" max="65535" value={port()} placeholder="8080"
+            onInput={e => update({ port: Number(e.currentTarget.value) || 0 })} class={`w-24 ${fieldClass()}`} />
+        </Show>
+        <Show when={props.probe?.kind === 'http'}>
+          <input type="text" value={(props.probe as { path?: string }).path ?? ''} placeholder="/health"
+            onInput={e => update({ path: e.currentTarget.value })} class={`flex-1 min-w-0 ${fieldClass()}`} />
+        </Show>
+        <Show when={props.probe?.kind === 'log'}>
+          <input type="text" value={(props.probe as { pattern: string }).pattern} placeholder="Listening on"
+            onInput={e => update({ pattern: e.currentTarget.value })} class={`flex-1 min-w-0 font-mono ${fieldClass()}`} />
+        </Show>
+      </div>
     </div>
   );
 };
 
 // App Modal
+// Fields of AppModal that show their save error inline
+const INLINE_FIELDS = ['name', 'executablePath', 'workingDirectory', 'arguments', 'environmentVars'];
+
+const FieldErrorText: Component<{ message?: string }> = (props) => (
+  <Show when={props.message}>
+    <p class="text-xs text-red-400 mt-1">{props.message}</p>
+  </Show>
+);
+
 const AppModal: Component = () => {
   const [store, actions] = useApp();
 
@@ -451,6 +786,36 @@ const AppModal: Component = () => {
   const [workingDir, setWorkingDir] = createSignal(editingApp()?.workingDirectory || '');
   const [args, setArgs] = createSignal(editingApp()?.arguments || '');
   const [envVars, setEnvVars] = createSignal(editingApp()?.environmentVars || '');
+  const [shell, setShell] = createSignal(editingApp()?.shell || '');
+  const [stopSignal, setStopSignal] = createSignal(editingApp()?.stopSignal || '');
+  const [stopTimeout, setStopTimeout] = createSignal(editingApp()?.stopTimeout?.toString() || '');
+  const [restartPolicy, setRestartPolicy] = createSignal<RestartPolicy>(editingApp()?.restartPolicy || 'never');
+  const [maxRestarts, setMaxRestarts] = createSignal(editingApp()?.maxRestarts?.toString() || '');
+  const [encoding, setEncoding] = createSignal(editingApp()?.encoding || '');
+  const [forceColor, setForceColor] = createSignal(editingApp()?.forceColor ?? false);
+  const [terminal, setTerminal] = createSignal(editingApp()?.terminal ?? false);
+  const [keepStdin, setKeepStdin] = createSignal(editingApp()?.keepStdin ?? false);
+  const [triggers, setTriggers] = createSignal<TriggerRule[]>(editingApp()?.triggers ?? []);
+  const [readiness, setReadiness] = createSignal<HealthProbe | undefined>(editingApp()?.health?.readiness);
+  const [liveness, setLiveness] = createSignal<HealthProbe | undefined>(editingApp()?.health?.liveness);
+  const [restartOnFailure, setRestartOnFailure] = createSignal(editingApp()?.health?.restartOnFailure ?? false);
+  const updateTrigger = (index: number, data: Partial<TriggerRule>) =>
+    setTriggers(triggers().map((rule, i) => (i === index ? { ...rule, ...data } : rule)));
+  const otherApps = () => store.apps.filter(a => a.id !== editingApp()?.id);
+  const [dependsOn, setDependsOn] = createSignal<string[]>(editingApp()?.dependsOn ?? []);
+  const toggleDependency = (id: string, on: boolean) =>
+    setDependsOn(on ? [...dependsOn(), id] : dependsOn().filter(dep => dep !== id));
+  const [saveError, setSaveError] = createSignal<ConfigError | null>(null);
+  // Errors about this app are shown under their field, the rest (other
+  // apps, triggers, dependencies...) above the buttons
+  const ownErrors = () => (saveError()?.errors ?? []).filter(e => !e.appId || !otherApps().some(a => a.id === e.appId));
+  const fieldError = (field: string) => ownErrors().find(e => e.field === field)?.message;
+  const otherErrors = () => {
+    const error = saveError();
+    if (!error) return [];
+    if (error.errors.length === 0) return [error.message];
+    return error.errors.filter(e => !ownErrors().includes(e) || !INLINE_FIELDS.includes(e.field)).map(e => e.message);
+  };
   const [showAdvanced, setShowAdvanced] = createSignal(false);
 
   const isDark = () => store.settings.theme === 'dark';
@@ -531,13 +896,33 @@ const AppModal: Component = () => {
       name: name(), icon: icon(), appType: appType(),
       executablePath: execPath(), workingDirectory: workingDir(),
       arguments: args(), environmentVars: envVars(),
+      shell: shell(),
+      stopSignal: stopSignal(),
+      stopTimeout: stopTimeout().trim() === '' ? undefined : Math.max(0, Number(stopTimeout()) || 0),
+      restartPolicy: restartPolicy(),
+      maxRestarts: maxRestarts().trim() === '' ? undefined : Math.max(0, Number(maxRestarts()) || 0),
+      encoding: encoding().trim() || undefined,
+      forceColor: forceColor(),
+      terminal: terminal(),
+      keepStdin: keepStdin(),
+      triggers: triggers().filter(rule => rule.pattern.trim() !== ''),
+      health: {
+        ...editingApp()?.health,
+        readiness: validProbe(readiness()),
+        liveness: validProbe(liveness()),
+        restartOnFailure: restartOnFailure(),
+      },
+      dependsOn: dependsOn().filter(id => store.apps.some(a => a.id === id)),
       isRunning: isRunning,
     };
 
-    if (isEdit() && store.modal.type === 'edit') {
-      actions.updateApp(store.modal.appId, data);
-    } else {
-      actions.addApp(data);
+    const error = isEdit() && store.modal.type === 'edit'
+      ? await actions.updateApp(store.modal.appId, data)
+      : await actions.addApp(data);
+    if (error) {
+      setSaveError(error);
+      if (ownErrors().some(e => e.field !== 'name' && e.field !== 'executablePath')) setShowAdvanced(true);
+      return;
     }
     actions.closeModal();
   };
@@ -570,6 +955,7 @@ const AppModal: Component = () => {
               <input type="text" value={name()} onInput={e => setName(e.currentTarget.value)}
                 placeholder="My App" required
                 class={`w-full px-3 py-2 rounded-lg border outline-none text-sm transition-colors ${inputClass}`} />
+              <FieldErrorText message={fieldError('name')} />
             </div>
           </div>
 
@@ -605,6 +991,7 @@ const AppModal: Component = () => {
                 <button type="button" onClick={handleBrowseExe} class={`px-3 py-2 rounded-lg border transition-colors ${btnSecondaryClass}`}>📁</button>
               </Show>
             </div>
+            <FieldErrorText message={fieldError('executablePath')} />
           </div>
 
           {/* Advanced */}
@@ -623,22 +1010,168 @@ const AppModal: Component = () => {
                       placeholder="C:\Path" class={`flex-1 px-3 py-2 rounded-lg border outline-none text-sm transition-colors ${inputClass}`} />
                     <button type="button" onClick={handleBrowseWorkDir} class={`px-3 py-2 rounded-lg border transition-colors ${btnSecondaryClass}`}>📁</button>
                   </div>
+                  <FieldErrorText message={fieldError('workingDirectory')} />
                 </div>
+                <Show when={appType() === 'shell'}>
+                  <div>
+                    <label class={`text-xs mb-1 block ${labelClass}`}>Trình thông dịch</label>
+                    <input type="text" value={shell()} onInput={e => setShell(e.currentTarget.value)}
+                      placeholder="sh / bash / zsh / pwsh / cmd" class={`w-full px-3 py-2 rounded-lg border outline-none text-sm transition-colors ${inputClass}`} />
+                  </div>
+                </Show>
                 <div>
                   <label class={`text-xs mb-1 block ${labelClass}`}>Tham số</label>
                   <input type="text" value={args()} onInput={e => setArgs(e.currentTarget.value)}
                     placeholder="--port 8080" class={`w-full px-3 py-2 rounded-lg border outline-none text-sm transition-colors ${inputClass}`} />
+                  <FieldErrorText message={fieldError('arguments')} />
                 </div>
                 <div>
                   <label class={`text-xs mb-1 block ${labelClass}`}>Biến môi trường (KEY=value)</label>
                   <textarea value={envVars()} onInput={e => setEnvVars(e.currentTarget.value)}
                     placeholder="NODE_ENV=production" rows={2}
                     class={`w-full px-3 py-2 rounded-lg border outline-none text-sm resize-none transition-colors ${inputClass}`} />
+                  <FieldErrorText message={fieldError('environmentVars')} />
+                </div>
+                <div class="flex gap-2 items-end">
+                  <div class="flex-1">
+                    <label class={`text-xs mb-1 block ${labelClass}`}>Mã hóa đầu ra</label>
+                    <input type="text" list="encoding-options" value={encoding()} onInput={e => setEncoding(e.currentTarget.value)}
+                      placeholder="auto / utf-8 / 1258" class={`w-full px-3 py-2 rounded-lg border outline-none text-sm transition-colors ${inputClass}`} />
+                    <datalist id="encoding-options">
+                      <option value="auto">Tự động</option>
+                      <option value="utf-8">UTF-8</option>
+                      <option value="1258">Tiếng Việt (Windows-1258)</option>
+                      <option value="1252">Tây Âu (Windows-1252)</option>
+                      <option value="437">OEM Mỹ (437)</option>
+                      <option value="850">OEM đa ngôn ngữ (850)</option>
+                      <option value="936">Tiếng Trung giản thể (GBK)</option>
+                      <option value="932">Tiếng Nhật (Shift-JIS)</option>
+                    </datalist>
+                  </div>
+                  <label class="w-28 flex items-center gap-2 py-2 cursor-pointer" title="FORCE_COLOR / CLICOLOR_FORCE">
+                    <input type="checkbox" checked={forceColor()} onChange={e => setForceColor(e.currentTarget.checked)}
+                      class="w-4 h-4 rounded accent-blue-500" />
+                    <span class={`text-xs ${labelClass}`}>Giữ màu</span>
+                  </label>
                 </div>
+                <label class="flex items-center gap-2 cursor-pointer" title="Chạy trong pseudo-terminal để trả lời lời nhắc từ khung log">
+                  <input type="checkbox" checked={terminal()} onChange={e => setTerminal(e.currentTarget.checked)}
+                    class="w-4 h-4 rounded accent-blue-500" />
+                  <span class={`text-xs ${labelClass}`}>Chế độ terminal (ứng dụng tương tác)</span>
+                </label>
+                <Show when={!terminal()}>
+                  <label class="flex items-center gap-2 cursor-pointer" title="Giữ stdin mở để gửi lệnh như reload, stop">
+                    <input type="checkbox" checked={keepStdin()} onChange={e => setKeepStdin(e.currentTarget.checked)}
+                      class="w-4 h-4 rounded accent-blue-500" />
+                    <span class={`text-xs ${labelClass}`}>Cho phép gửi lệnh (stdin)</span>
+                  </label>
+                </Show>
+                <div class="flex gap-2">
+                  <div class="flex-1">
+                    <label class={`text-xs mb-1 block ${labelClass}`}>Tín hiệu dừng</label>
+                    <select value={stopSignal()} onChange={e => setStopSignal(e.currentTarget.value)}
+                      class={`w-full px-3 py-2 rounded-lg border outline-none text-sm transition-colors ${inputClass}`}>
+                      <option value="">Mặc định</option>
+                      <option value="SIGTERM">SIGTERM</option>
+                      <option value="SIGINT">SIGINT</option>
+                      <option value="CTRL_BREAK">Ctrl+Break</option>
+                      <option value="WM_CLOSE">WM_CLOSE</option>
+                    </select>
+                  </div>
+                  <div class="w-28">
+                    <label class={`text-xs mb-1 block ${labelClass}`}>Chờ (giây)</label>
+                    <input type="number" min="0" value={stopTimeout()} onInput={e => setStopTimeout(e.currentTarget.value)}
+                      placeholder="5" class={`w-full px-3 py-2 rounded-lg border outline-none text-sm transition-colors ${inputClass}`} />
+                  </div>
+                </div>
+                <div class="flex gap-2">
+                  <div class="flex-1">
+                    <label class={`text-xs mb-1 block ${labelClass}`}>Tự khởi động lại</label>
+                    <select value={restartPolicy()} onChange={e => setRestartPolicy(e.currentTarget.value as RestartPolicy)}
+                      class={`w-full px-3 py-2 rounded-lg border outline-none text-sm transition-colors ${inputClass}`}>
+                      <option value="never">Không</option>
+                      <option value="on-failure">Khi lỗi</option>
+                      <option value="always">Luôn luôn</option>
+                    </select>
+                  </div>
+                  <div class="w-28">
+                    <label class={`text-xs mb-1 block ${labelClass}`}>Tối đa (lần)</label>
+                    <input type="number" min="0" value={maxRestarts()} onInput={e => setMaxRestarts(e.currentTarget.value)}
+                      placeholder="∞" disabled={restartPolicy() === 'never'}
+                      class={`w-full px-3 py-2 rounded-lg border outline-none text-sm transition-colors disabled:opacity-40 ${inputClass}`} />
+                  </div>
+                </div>
+                <div>
+                  <label class={`text-xs mb-1 block ${labelClass}`}>Theo dõi log (regex)</label>
+                  <div class="space-y-2">
+                    <Index each={triggers()}>
+                      {(rule, i) => (
+                        <div class="flex gap-2 items-center">
+                          <input type="text" value={rule().pattern} onInput={e => updateTrigger(i, { pattern: e.currentTarget.value })}
+                            placeholder="ERROR|panic|Traceback"
+                            class={`flex-1 min-w-0 px-3 py-2 rounded-lg border outline-none text-sm font-mono transition-colors ${inputClass}`} />
+                          <select value={rule().action} onChange={e => updateTrigger(i, { action: e.currentTarget.value as TriggerAction })}
+                            class={`w-28 px-2 py-2 rounded-lg border outline-none text-sm transition-colors ${inputClass}`}>
+                            <option value="none">Chỉ báo</option>
+                            <option value="restart">Khởi động lại</option>
+                            <option value="stop">Dừng</option>
+                            <option value="startApp">Chạy app khác</option>
+                          </select>
+                          <Show when={rule().action === 'startApp'}>
+                            <select value={rule().targetAppId ?? ''} onChange={e => updateTrigger(i, { targetAppId: e.currentTarget.value || undefined })}
+                              class={`w-28 px-2 py-2 rounded-lg border outline-none text-sm transition-colors ${inputClass}`}>
+                              <option value="">Chọn app</option>
+                              <For each={otherApps()}>{(app) => <option value={app.id}>{app.name}</option>}</For>
+                            </select>
+                          </Show>
+                          <label class="flex items-center gap-1 cursor-pointer" title="Tính vào số lỗi">
+                            <input type="checkbox" checked={rule().isError} onChange={e => updateTrigger(i, { isError: e.currentTarget.checked })}
+                              class="w-4 h-4 rounded accent-red-500" />
+                            <span class={`text-xs ${labelClass}`}>Lỗi</span>
+                          </label>
+                          <button type="button" onClick={() => setTriggers(triggers().filter((_, j) => j !== i))}
+                            class={`text-xs px-2 py-1 rounded ${labelClass} hover:text-red-400`}>✕</button>
+                        </div>
+                      )}
+                    </Index>
+                    <button type="button" onClick={() => setTriggers([...triggers(), { pattern: '', action: 'none', isError: true }])}
+                      class={`text-xs ${labelClass} hover:text-blue-400`}>+ Thêm quy tắc</button>
+                  </div>
+                </div>
+                <Show when={otherApps().length > 0}>
+                  <div>
+                    <label class={`text-xs mb-1 block ${labelClass}`}>Khởi động sau (phụ thuộc)</label>
+                    <div class="flex flex-wrap gap-x-4 gap-y-1">
+                      <For each={otherApps()}>
+                        {(app) => (
+                          <label class="flex items-center gap-2 cursor-pointer">
+                            <input type="checkbox" checked={dependsOn().includes(app.id)}
+                              onChange={e => toggleDependency(app.id, e.currentTarget.checked)}
+                              class="w-4 h-4 rounded accent-blue-500" />
+                            <span class={`text-xs ${labelClass}`}>{app.name}</span>
+                          </label>
+                        )}
+                      </For>
+                    </div>
+                  </div>
+                </Show>
+                <ProbeEditor label="Sẵn sàng khi" probe={readiness()} onChange={setReadiness} inputClass={inputClass} labelClass={labelClass} />
+                <ProbeEditor label="Kiểm tra hoạt động" probe={liveness()} onChange={setLiveness} inputClass={inputClass} labelClass={labelClass} />
+                <Show when={liveness()}>
+                  <label class="flex items-center gap-2 cursor-pointer">
+                    <input type="checkbox" checked={restartOnFailure()} onChange={e => setRestartOnFailure(e.currentTarget.checked)}
+                      class="w-4 h-4 rounded accent-blue-500" />
+                    <span class={`text-xs ${labelClass}`}>Khởi động lại khi không phản hồi</span>
+                  </label>
+                </Show>
               </div>
             </Show>
           </div>
 
+          <For each={otherErrors()}>
+            {(message) => <p class="text-xs text-red-400">❌ {message}</p>}
+          </For>
+
           {/* Buttons */}
           <div class="flex gap-2 pt-2">
             <button type="button" onClick={actions.closeModal}
@@ -654,10 +1187,126 @@ const AppModal: Component = () => {
   );
 };
 
+// Group Modal
+const GroupModal: Component = () => {
+  const [store, actions] = useApp();
+  const isDark = () => store.settings.theme === 'dark';
+  const modal = store.modal;
+  const group = modal.type === 'group' && modal.groupId
+    ? store.groups.find((g) => g.id === modal.groupId)
+    : undefined;
+
+  const [name, setName] = createSignal(group?.name ?? '');
+  const [members, setMembers] = createSignal<string[]>(group?.apps ?? []);
+  const [saveError, setSaveError] = createSignal<ConfigError | null>(null);
+
+  // A new group's id is only known to the store, so errors of any group
+  // but the others are this one's
+  const ownErrors = () => (saveError()?.errors ?? []).filter(e => e.groupId && !store.groups.some(g => g.id === e.groupId && g.id !== group?.id));
+  const nameError = () => ownErrors().find(e => e.field === 'name')?.message;
+  const otherErrors = () => {
+    const error = saveError();
+    if (!error) return [];
+    if (error.errors.length === 0) return [error.message];
+    return error.errors.filter(e => !ownErrors().includes(e) || e.field !== 'name').map(e => e.message);
+  };
+
+  const toggleMember = (appId: string, checked: boolean) => {
+    setMembers((ids) => checked ? [...ids, appId] : ids.filter((id) => id !== appId));
+  };
+
+  const handleSubmit = async (e: Event) => {
+    e.preventDefault();
+    const data = { name: name().trim(), apps: members() };
+    const error = group ? await actions.updateGroup(group.id, data) : await actions.addGroup(data);
+    if (error) {
+      setSaveError(error);
+      return;
+    }
+    actions.closeModal();
+  };
+
+  const handleDelete = async () => {
+    if (!group) return;
+    const { ask } = await import('@tauri-apps/plugin-dialog');
+    const confirmed = await ask(`Xóa nhóm "${group.name}"? Các ứng dụng trong nhóm vẫn được giữ lại.`, {
+      title: 'Xác nhận xóa',
+      kind: 'warning',
+    });
+    if (confirmed) {
+      const error = await actions.deleteGroup(group.id);
+      if (error) {
+        setSaveError(error);
+        return;
+      }
+      actions.closeModal();
+    }
+  };
+
+  const modalClass = isDark() ? 'bg-slate-800 border-white/10 text-white' : 'bg-white border-gray-200 text-gray-900';
+  const inputClass = isDark()
+    ? 'bg-white/5 border-white/10 focus:border-blue-500/50 placeholder-white/20'
+    : 'bg-gray-50 border-gray-200 focus:border-blue-500 focus:bg-white placeholder-gray-400';
+  const labelClass = isDark() ? 'text-white/50' : 'text-gray-500';
+  const btnSecondaryClass = isDark() ? 'bg-white/5 hover:bg-white/10 border-white/10' : 'bg-gray-100 hover:bg-gray-200 border-gray-200 text-gray-700';
+
+  return (
+    <div class="fixed inset-0 bg-black/60 backdrop-blur-sm flex items-center justify-center z-50 p-4" onClick={actions.closeModal}>
+      <div class={`${modalClass} rounded-2xl p-5 w-full max-w-sm shadow-2xl border transition-colors duration-300`} onClick={e => e.stopPropagation()}>
+        <h2 class="text-lg font-semibold mb-4">{group ? '✏️ Sửa' : '➕ Thêm'} nhóm</h2>
+
+        <form onSubmit={handleSubmit} class="space-y-3">
+          <div>
+            <label class={`text-xs mb-1 block ${labelClass}`}>Tên nhóm</label>
+            <input type="text" value={name()} onInput={e => setName(e.currentTarget.value)}
+              placeholder="Backend" required
+              class={`w-full px-3 py-2 rounded-lg border outline-none text-sm transition-colors ${inputClass}`} />
+            <FieldErrorText message={nameError()} />
+          </div>
+
+          <div>
+            <label class={`text-xs mb-1 block ${labelClass}`}>Ứng dụng (chạy theo thứ tự phụ thuộc)</label>
+            <div class="flex flex-col gap-1 max-h-60 overflow-auto">
+              <For each={store.apps}>
+                {(app) => (
+                  <label class="flex items-center gap-2 cursor-pointer">
+                    <input type="checkbox" checked={members().includes(app.id)}
+                      onChange={e => toggleMember(app.id, e.currentTarget.checked)}
+                      class="w-4 h-4 rounded accent-blue-500" />
+                    <span class="text-sm">{app.name}</span>
+                  </label>
+                )}
+              </For>
+            </div>
+          </div>
+
+          <For each={otherErrors()}>
+            {(message) => <p class="text-xs text-red-400">❌ {message}</p>}
+          </For>
+
+          <div class="flex gap-2 pt-2">
+            <Show when={group}>
+              <button type="button" onClick={handleDelete}
+                class="py-2.5 px-3 rounded-lg text-sm text-red-400 hover:bg-red-500/10 transition-colors">Xóa</button>
+            </Show>
+            <button type="button" onClick={actions.closeModal}
+              class={`flex-1 py-2.5 rounded-lg border text-sm transition-colors ${btnSecondaryClass}`}>Hủy</button>
+            <button type="submit"
+              class="flex-1 py-2.5 rounded-lg bg-gradient-to-r from-blue-500 to-purple-600 text-white text-sm font-medium hover:shadow-lg hover:shadow-blue-500/25 transition-all">
+              {group ? 'Lưu' : 'Thêm'}
+            </button>
+          </div>
+        </form>
+      </div>
+    </div>
+  );
+};
+
 // Settings Modal
 const SettingsModal: Component = () => {
   const [store, actions] = useApp();
   const isDark = () => store.settings.theme === 'dark';
+  const [saveError, setSaveError] = createSignal<ConfigError | null>(null);
 
   const modalClass = isDark() ? 'bg-slate-800 border-white/10 text-white' : 'bg-white border-gray-200 text-gray-900';
   const itemClass = isDark() ? 'bg-white/5 border-white/10 hover:bg-white/10' : 'bg-gray-50 border-gray-200 hover:bg-gray-100';
@@ -675,9 +1324,12 @@ const SettingsModal: Component = () => {
             <p class={`text-xs ${textSubClass}`}>Khi đóng app, thu vào system tray</p>
           </div>
           <input type="checkbox" checked={store.settings.minimizeToTray}
-            onChange={e => actions.updateSettings({ minimizeToTray: e.currentTarget.checked })}
+            onChange={async e => setSaveError(await actions.updateSettings({ minimizeToTray: e.currentTarget.checked }))}
             class="w-5 h-5 rounded accent-blue-500" />
         </label>
+        <Show when={saveError()}>
+          <p class="text-xs text-red-400 mt-2">❌ {saveError()!.message}</p>
+        </Show>
 
         <div class={`mt-4 p-3 rounded-xl border ${itemClass}`}>
           <p class="text-sm font-medium">AppCtrl v1.0.1</p>
@@ -690,10 +1342,18 @@ const SettingsModal: Component = () => {
   );
 };
 
+// kill_process_by_pid / kill_process_by_name reject with a ProcessError
+const showKillError = async (e: unknown) => {
+  const { message } = await import('@tauri-apps/plugin-dialog');
+  await message(`Không thể tắt tiến trình: ${(e as ProcessError).message ?? String(e)}`, { title: 'Lỗi', kind: 'error' });
+};
+
 // Port Killer Modal
 const PortKillerModal: Component = () => {
   const [store, actions] = useApp();
   const [ports, setPorts] = createSignal<PortInfo[]>([]);
+  const [connections, setConnections] = createSignal<ConnectionInfo[]>([]);
+  const [view, setView] = createSignal<'ports' | 'connections'>('ports');
   const [search, setSearch] = createSignal('');
   const [loading, setLoading] = createSignal(true);
   const isDark = () => store.settings.theme === 'dark';
@@ -708,8 +1368,11 @@ const PortKillerModal: Component = () => {
   const loadPorts = async () => {
     setLoading(true);
     try {
-      const data = await invoke<PortInfo[]>('get_listening_ports');
-      setPorts(data);
+      if (view() === 'ports') {
+        setPorts(await invoke<PortInfo[]>('get_listening_ports'));
+      } else {
+        setConnections(await invoke<ConnectionInfo[]>('get_connections'));
+      }
     } catch (e) {
       console.error('Failed to load ports:', e);
     } finally {
@@ -725,6 +1388,7 @@ const PortKillerModal: Component = () => {
       await loadPorts(); // Refresh
     } catch (e) {
       console.error('Failed to kill process:', e);
+      await showKillError(e);
     }
   };
 
@@ -733,10 +1397,32 @@ const PortKillerModal: Component = () => {
     return ports().filter(p =>
       p.name.toLowerCase().includes(s) ||
       p.port.toString().includes(s) ||
-      p.pid.toString().includes(s)
+      p.pid.toString().includes(s) ||
+      p.localAddress.includes(s)
+    );
+  };
+
+  const filteredConnections = () => {
+    const s = search().toLowerCase();
+    return connections().filter(c =>
+      c.name.toLowerCase().includes(s) ||
+      c.localPort.toString().includes(s) ||
+      (c.remoteAddress || '').includes(s) ||
+      (c.remotePort || '').toString().includes(s) ||
+      c.pid.toString().includes(s)
     );
   };
 
+  const formatEndpoint = (family: string, address: string | null, port: number | null) => {
+    if (address === null || port === null) return '*';
+    return family === 'IPv6' ? `[${address}]:${port}` : `${address}:${port}`;
+  };
+
+  const switchView = (v: 'ports' | 'connections') => {
+    setView(v);
+    loadPorts();
+  };
+
   return (
     <div class="fixed inset-0 bg-black/60 backdrop-blur-sm flex items-center justify-center z-50 p-4" onClick={actions.closeModal}>
       <div class={`${modalClass} rounded-2xl p-5 w-full max-w-lg shadow-2xl border transition-colors duration-300 flex flex-col max-h-[80vh]`} onClick={e => e.stopPropagation()}>
@@ -752,6 +1438,22 @@ const PortKillerModal: Component = () => {
           </div>
         </div>
 
+        <div class="grid grid-cols-2 gap-2 mb-3">
+          <For each={(['ports', 'connections'] as const)}>
+            {(v) => (
+              <button type="button" onClick={() => switchView(v)}
+                class={`py-1.5 rounded-lg text-xs font-medium border transition-colors
+                  ${view() === v
+                    ? 'bg-blue-500/20 border-blue-500/50 text-blue-500'
+                    : isDark()
+                      ? 'bg-white/5 border-white/10 text-white/60 hover:bg-white/10'
+                      : 'bg-gray-50 border-gray-200 text-gray-600 hover:bg-gray-100'}`}>
+                {v === 'ports' ? 'Listening' : 'Connections'}
+              </button>
+            )}
+          </For>
+        </div>
+
         <input
           type="text"
           value={search()}
@@ -764,8 +1466,35 @@ const PortKillerModal: Component = () => {
           <Show when={!loading()} fallback={
             <div class={`flex items-center justify-center h-full ${textSubClass}`}>Loading...</div>
           }>
-            <Show when={filteredPorts().length > 0} fallback={
-              <div class={`text-center py-10 ${textSubClass}`}>No processes found</div>
+            <Show when={view() === 'connections'}>
+              <Show when={filteredConnections().length > 0} fallback={
+                <div class={`text-center py-10 ${textSubClass}`}>No connections found</div>
+              }>
+                <For each={filteredConnections()}>
+                  {(c) => (
+                    <div class={`flex items-center justify-between p-3 rounded-xl border ${itemClass}`}>
+                      <div class="min-w-0">
+                        <p class="font-medium truncate text-sm">{c.name}</p>
+                        <p class={`text-xs truncate ${textSubClass}`}>
+                          {formatEndpoint(c.family, c.localAddress, c.localPort)} → {formatEndpoint(c.family, c.remoteAddress, c.remotePort)}
+                        </p>
+                        <p class={`text-xs truncate ${textSubClass}`}>PID: {c.pid} • {c.protocol}{c.state ? ` • ${c.state}` : ''}</p>
+                      </div>
+                      <button
+                        onClick={() => handleKill(c.pid)}
+                        class="px-3 py-1.5 rounded-lg bg-red-500/10 text-red-500 hover:bg-red-500/20 text-xs font-medium border border-red-500/20 transition-colors flex-shrink-0"
+                      >
+                        Kill
+                      </button>
+                    </div>
+                  )}
+                </For>
+              </Show>
+            </Show>
+            <Show when={view() === 'ports' && filteredPorts().length > 0} fallback={
+              <Show when={view() === 'ports'}>
+                <div class={`text-center py-10 ${textSubClass}`}>No processes found</div>
+              </Show>
             }>
               <For each={filteredPorts()}>
                 {(p) => (
@@ -776,7 +1505,7 @@ const PortKillerModal: Component = () => {
                       </div>
                       <div class="min-w-0">
                         <p class="font-medium truncate text-sm">{p.name}</p>
-                        <p class={`text-xs truncate ${textSubClass}`}>PID: {p.pid} • {p.protocol}</p>
+                        <p class={`text-xs truncate ${textSubClass}`}>PID: {p.pid} • {p.protocol} • {p.family === 'IPv6' ? `[${p.localAddress}]` : p.localAddress}</p>
                       </div>
                     </div>
                     <button
@@ -835,6 +1564,7 @@ const TaskKillerModal: Component = () => {
       await loadTasks(); // Refresh
     } catch (e) {
       console.error('Failed to kill task:', e);
+      await showKillError(e);
     }
   };
 
@@ -844,6 +1574,7 @@ const TaskKillerModal: Component = () => {
       await loadTasks(); // Refresh
     } catch (e) {
       console.error('Failed to kill task group:', e);
+      await showKillError(e);
     }
   };
 
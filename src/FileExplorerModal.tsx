@@ -2,7 +2,7 @@ import { Component, createSignal, createEffect, onMount, For, Show, onCleanup }
 import { invoke } from '@tauri-apps/api/core';
 import { useApp } from './store/appStore';
 import { ask, message } from '@tauri-apps/plugin-dialog';
-import type { DiskInfo, FileInfo } from './types';
+import type { DiskInfo, FileInfo, ProcessError } from './types';
 
 // Global cache for icons (persists across modal openings)
 const iconCache: Record<string, string> = {};
@@ -693,7 +693,7 @@ export const FileExplorerModal: Component = () => {
                                                                             setLockProcesses(list);
                                                                         }
                                                                     } catch (err) {
-                                                                        await message(`Không thể tắt tiến trình: ${err}`, { title: 'Lỗi', kind: 'error' });
+                                                                        await message(`Không thể tắt tiến trình: ${(err as ProcessError).message ?? String(err)}`, { title: 'Lỗi', kind: 'error' });
                                                                     }
                                                                 }}
                                                                 class="px-2 py-0.5 rounded bg-red-500/10 text-red-500 hover:bg-red-500 hover:text-white transition-colors text-[10px] font-semibold border border-red-500/20 active:scale-95"
@@ -1,35 +1,61 @@
-import { createContext, useContext, type ParentComponent, onMount } from 'solid-js';
+import { createContext, useContext, type ParentComponent, onMount, onCleanup } from 'solid-js';
 import { createStore } from 'solid-js/store';
 import { invoke } from '@tauri-apps/api/core';
-import type { AppConfig, ModalState, AppSettings } from '../types';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import type { AppConfig, ModalState, AppSettings, LogEntry, HealthStatus, GroupConfig, GroupStatus, ConfigError, ConfigRecovery, ConfigDiff } from '../types';
 
 interface AppStore {
     apps: AppConfig[];
     modal: ModalState;
-    logs: { [appId: string]: string[] };
+    logs: { [appId: string]: LogEntry[] };
+    // Highest backend seq seen per app; survives clearLogs so a backfill
+    // doesn't bring cleared lines back
+    logSeq: { [appId: string]: number };
+    // Lines matched by error triggers, kept by the backend until reset
+    errorCounts: { [appId: string]: number };
+    health: { [appId: string]: HealthStatus };
+    groups: GroupConfig[];
+    groupStatus: { [groupId: string]: GroupStatus };
     settings: AppSettings;
 }
 
+// isRunning is only known at runtime and never saved
+type SavedApp = Omit<AppConfig, 'isRunning'>;
+
 interface ConfigData {
-    apps?: AppConfig[];
+    // Set by the backend, which upgrades older files on load
+    schemaVersion?: number;
+    apps?: SavedApp[];
+    groups?: GroupConfig[];
     settings?: AppSettings;
 }
 
 interface AppActions {
-    addApp: (app: Omit<AppConfig, 'id' | 'isRunning'>) => void;
-    updateApp: (id: string, data: Partial<AppConfig>) => void;
-    deleteApp: (id: string) => void;
+    // These resolve to the backend's field errors when it rejects the
+    // config (e.g. a missing folder or a dependency cycle), after undoing
+    // the change
+    addApp: (app: Omit<AppConfig, 'id' | 'isRunning'>) => Promise<ConfigError | null>;
+    updateApp: (id: string, data: Partial<AppConfig>) => Promise<ConfigError | null>;
+    deleteApp: (id: string) => Promise<ConfigError | null>;
+    addGroup: (group: Omit<GroupConfig, 'id'>) => Promise<ConfigError | null>;
+    updateGroup: (id: string, data: Partial<GroupConfig>) => Promise<ConfigError | null>;
+    deleteGroup: (id: string) => Promise<ConfigError | null>;
+    refreshGroupStatus: () => void;
     openAddModal: () => void;
     openEditModal: (appId: string) => void;
+    openGroupModal: (groupId?: string) => void;
     openSettingsModal: () => void;
     openPortKillerModal: () => void;
     openTaskKillerModal: () => void;
     openFileExplorerModal: () => void;
     closeModal: () => void;
     setAppRunning: (id: string, running: boolean) => void;
-    appendLog: (appId: string, line: string) => void;
+    appendLog: (appId: string, entry: LogEntry | string) => void;
     clearLogs: (appId: string) => void;
-    updateSettings: (settings: Partial<AppSettings>) => void;
+    setErrorCount: (appId: string, count: number) => void;
+    resetErrorCount: (appId: string) => void;
+    setHealth: (appId: string, status: HealthStatus) => void;
+    updateSettings: (settings: Partial<AppSettings>) => Promise<ConfigError | null>;
     getApp: (id: string) => AppConfig | undefined;
 }
 
@@ -44,31 +70,103 @@ export const AppProvider: ParentComponent = (props) => {
         apps: [],
         modal: { type: 'closed' },
         logs: {},
+        logSeq: {},
+        errorCounts: {},
+        health: {},
+        groups: [],
+        groupStatus: {},
         settings: { minimizeToTray: true, theme: 'dark' },
     });
 
-    const saveConfig = async () => {
+    // Keys owned by the backend (e.g. systemProcesses) are kept as loaded
+    let extraConfig: Record<string, unknown> = {};
+
+    const saveConfig = async (): Promise<ConfigError | null> => {
         const config: ConfigData = {
-            apps: store.apps,
+            ...extraConfig,
+            apps: store.apps.map(({ isRunning: _, ...app }) => app),
+            groups: store.groups,
             settings: store.settings,
         };
         try {
-            await invoke('save_config', { config: JSON.stringify(config, null, 2) });
+            await invoke('save_config', { config });
+            return null;
         } catch (e) {
             console.error('Failed to save config:', e);
+            return typeof e === 'object' && e !== null
+                ? e as ConfigError
+                : { kind: 'io', message: String(e), errors: [] };
         }
     };
 
+    // Apply a change, keeping it only if the backend accepts the config.
+    // Changes must replace the apps and groups they touch rather than
+    // edit them in place, or there is nothing to go back to.
+    const saveChange = async (change: () => void) => {
+        const previous = { apps: store.apps, groups: store.groups, settings: { ...store.settings } };
+        change();
+        const error = await saveConfig();
+        if (error) setStore(previous);
+        return error;
+    };
+
+    const applyConfig = (config: ConfigData) => {
+        extraConfig = { ...config };
+        delete extraConfig.apps;
+        delete extraConfig.groups;
+        delete extraConfig.settings;
+        // Keep what is known about running apps across a reload
+        const running = new Set(store.apps.filter((app) => app.isRunning).map((app) => app.id));
+        if (config.apps) setStore('apps', config.apps.map((app) => ({ ...app, isRunning: running.has(app.id) })));
+        if (config.groups) setStore('groups', config.groups);
+        if (config.settings) setStore('settings', config.settings);
+    };
+
+    // config.json edited by hand, a script or a sync client
+    let unlistenChanged: UnlistenFn | undefined;
+    let unlistenInvalid: UnlistenFn | undefined;
+    onCleanup(() => {
+        if (unlistenChanged) unlistenChanged();
+        if (unlistenInvalid) unlistenInvalid();
+    });
+
     onMount(async () => {
+        unlistenChanged = await listen<{ diff: ConfigDiff; config: ConfigData }>('config-changed', (event) => {
+            applyConfig(event.payload.config);
+            actions.refreshGroupStatus();
+        });
+        unlistenInvalid = await listen<ConfigError>('config-invalid', async (event) => {
+            const { message } = await import('@tauri-apps/plugin-dialog');
+            await message(`config.json vừa được sửa bên ngoài nhưng không hợp lệ, vẫn dùng cấu hình cũ.\n${event.payload.message}`, {
+                title: 'Lỗi cấu hình',
+                kind: 'warning',
+            });
+        });
+
         try {
-            const json = await invoke<string>('load_config');
-            if (json && json.trim() !== '{}') {
-                const config: ConfigData = JSON.parse(json);
-                if (config.apps) setStore('apps', config.apps);
-                if (config.settings) setStore('settings', config.settings);
-            }
+            applyConfig(await invoke<ConfigData>('load_config'));
         } catch (e) {
             console.error('Failed to load config:', e);
+            const { message } = await import('@tauri-apps/plugin-dialog');
+            await message(`Không đọc được config.json: ${(e as ConfigError).message ?? e}`, {
+                title: 'Lỗi cấu hình',
+                kind: 'error',
+            });
+            return;
+        }
+
+        // The backend may have replaced a damaged file, at startup or just now
+        const recovery = await invoke<ConfigRecovery | null>('take_config_recovery').catch(() => null);
+        if (recovery) {
+            const restored = recovery.backup
+                ? `Đã khôi phục từ bản sao lưu ${recovery.backup}.`
+                : 'Không có bản sao lưu hợp lệ, bắt đầu với cấu hình trống.';
+            const kept = recovery.corruptCopy ? `\nBản bị hỏng được giữ tại ${recovery.corruptCopy}.` : '';
+            const { message } = await import('@tauri-apps/plugin-dialog');
+            await message(`config.json bị hỏng (${recovery.error}). ${restored}${kept}`, {
+                title: 'Khôi phục cấu hình',
+                kind: 'warning',
+            });
         }
     });
 
@@ -79,28 +177,48 @@ export const AppProvider: ParentComponent = (props) => {
                 ...appData,
                 isRunning: false,
             };
-            setStore('apps', (apps) => {
-                const updated = [...apps, newApp];
-                return updated;
-            });
-            saveConfig();
+            return saveChange(() => setStore('apps', (apps) => [...apps, newApp]));
         },
 
         updateApp: (id, data) => {
-            setStore('apps', (app) => app.id === id, data);
-            saveConfig();
+            return saveChange(() => setStore('apps', (apps) => apps.map((app) => (app.id === id ? { ...app, ...data } : app))));
         },
 
         deleteApp: (id) => {
-            setStore('apps', (apps) => {
-                const updated = apps.filter((app) => app.id !== id);
-                return updated;
+            return saveChange(() => {
+                setStore('apps', (apps) => apps
+                    .filter((app) => app.id !== id)
+                    // Nothing can wait for an app that is gone
+                    .map((app) => (app.dependsOn?.includes(id)
+                        ? { ...app, dependsOn: app.dependsOn.filter((dep) => dep !== id) }
+                        : app)));
+                setStore('groups', (groups) => groups.map((group) => (group.apps.includes(id)
+                    ? { ...group, apps: group.apps.filter((appId) => appId !== id) }
+                    : group)));
             });
-            saveConfig();
+        },
+
+        addGroup: (groupData) => {
+            return saveChange(() => setStore('groups', (groups) => [...groups, { id: generateId(), ...groupData }]));
+        },
+
+        updateGroup: (id, data) => {
+            return saveChange(() => setStore('groups', (groups) => groups.map((group) => (group.id === id ? { ...group, ...data } : group))));
+        },
+
+        deleteGroup: (id) => {
+            return saveChange(() => setStore('groups', (groups) => groups.filter((group) => group.id !== id)));
+        },
+
+        refreshGroupStatus: () => {
+            invoke<GroupStatus[]>('get_group_statuses')
+                .then((statuses) => setStore('groupStatus', Object.fromEntries(statuses.map((s) => [s.id, s]))))
+                .catch(console.error);
         },
 
         openAddModal: () => setStore('modal', { type: 'add' }),
         openEditModal: (appId) => setStore('modal', { type: 'edit', appId }),
+        openGroupModal: (groupId) => setStore('modal', { type: 'group', groupId }),
         openSettingsModal: () => setStore('modal', { type: 'settings' }),
         openPortKillerModal: () => setStore('modal', { type: 'port-killer' }),
         openTaskKillerModal: () => setStore('modal', { type: 'task-killer' }),
@@ -111,17 +229,48 @@ export const AppProvider: ParentComponent = (props) => {
             setStore('apps', (app) => app.id === id, 'isRunning', running);
         },
 
-        appendLog: (appId, line) => {
-            setStore('logs', appId, (logs) => [...(logs || []), line]);
+        appendLog: (appId, entry) => {
+            if (typeof entry === 'string') {
+                entry = { timestamp: new Date().toISOString(), stream: 'system', text: entry };
+            }
+            if (entry.seq !== undefined) {
+                // Already shown, either live or from a backfill
+                if (entry.seq <= (store.logSeq[appId] ?? 0)) return;
+                setStore('logSeq', appId, entry.seq);
+            }
+            const added = entry;
+            setStore('logs', appId, (logs = []) => {
+                // A partial line is superseded by the next line of its stream
+                for (let i = logs.length - 1; i >= 0; i--) {
+                    if (logs[i].stream !== added.stream) continue;
+                    if (!logs[i].partial) break;
+                    const next = logs.slice();
+                    next[i] = added;
+                    return next;
+                }
+                return [...logs, added];
+            });
         },
 
         clearLogs: (appId) => {
             setStore('logs', appId, []);
         },
 
+        setErrorCount: (appId, count) => {
+            setStore('errorCounts', appId, count);
+        },
+
+        resetErrorCount: (appId) => {
+            setStore('errorCounts', appId, 0);
+            invoke('reset_app_error_count', { appId }).catch(console.error);
+        },
+
+        setHealth: (appId, status) => {
+            setStore('health', appId, status);
+        },
+
         updateSettings: (newSettings) => {
-            setStore('settings', newSettings);
-            saveConfig();
+            return saveChange(() => setStore('settings', newSettings));
         },
 
         getApp: (id) => store.apps.find(a => a.id === id),
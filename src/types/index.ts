@@ -2,6 +2,51 @@
 export type AppType = 'exe' | 'bat' | 'shell';
 
 // Application configuration
+export type RestartPolicy = 'never' | 'on-failure' | 'always';
+
+export type TriggerAction = 'none' | 'restart' | 'stop' | 'startApp';
+
+// Regex checked against every output line of the app
+export interface TriggerRule {
+    pattern: string;
+    action: TriggerAction;
+    // App to start for 'startApp'
+    targetAppId?: string;
+    // Count matches in the app's error badge; off for rules like "Listening on port (\d+)"
+    isError: boolean;
+}
+
+export type HealthProbe =
+    | { kind: 'tcp'; port: number }
+    // GET http://localhost:port/path must answer 2xx
+    | { kind: 'http'; port: number; path?: string }
+    // A matching output line; for liveness, one since the previous check
+    | { kind: 'log'; pattern: string };
+
+export interface HealthOptions {
+    readiness?: HealthProbe;
+    liveness?: HealthProbe;
+    intervalSecs?: number;
+    timeoutSecs?: number;
+    // Consecutive liveness failures before the app is unhealthy
+    failureThreshold?: number;
+    startTimeoutSecs?: number;
+    restartOnFailure?: boolean;
+}
+
+export type HealthState = 'starting' | 'ready' | 'unhealthy' | 'stopped';
+
+// Returned by get_app_health
+export interface HealthStatus {
+    state: HealthState;
+    // Last probe error while unhealthy
+    reason: string | null;
+}
+
+export interface AppHealthEvent extends HealthStatus {
+    appId: string;
+}
+
 export interface AppConfig {
     id: string;
     name: string;
@@ -11,14 +56,91 @@ export interface AppConfig {
     workingDirectory: string;
     arguments: string;
     environmentVars: string;
+    // Interpreter for 'shell' apps (sh, bash, zsh, pwsh, cmd); empty = OS default
+    shell?: string;
+    // Graceful stop request (SIGTERM, SIGINT, CTRL_BREAK, WM_CLOSE); empty = OS default
+    stopSignal?: string;
+    // Seconds to wait before killing the process tree; 0 = kill immediately
+    stopTimeout?: number;
+    restartPolicy?: RestartPolicy;
+    // Consecutive restarts before giving up; empty = no limit
+    maxRestarts?: number;
+    // Output decoding: 'auto' (UTF-8, else the console code page), 'utf-8', or a code page like '1258'
+    encoding?: string;
+    // Set FORCE_COLOR/CLICOLOR_FORCE so tools keep colors when writing to a pipe
+    forceColor?: boolean;
+    // Run on a pseudo-terminal so interactive prompts can be answered from the log panel
+    terminal?: boolean;
+    // Keep stdin piped so console commands ("reload", "stop") can be sent
+    keepStdin?: boolean;
+    triggers?: TriggerRule[];
+    health?: HealthOptions;
+    // Ids of apps started (and ready) before this one; cycles are rejected on save
+    dependsOn?: string[];
     isRunning: boolean;
 }
 
+// A named set of apps started and stopped together
+export interface GroupConfig {
+    id: string;
+    name: string;
+    apps: string[];
+}
+
+// Worst member state wins; 'partial' = some members stopped
+export type GroupState = 'stopped' | 'starting' | 'ready' | 'partial' | 'unhealthy';
+
+// Returned by get_group_statuses
+export interface GroupStatus {
+    id: string;
+    state: GroupState;
+    running: number;
+    total: number;
+}
+
+// One invalid field, as reported by save_config
+export interface ConfigFieldError {
+    appId?: string;
+    groupId?: string;
+    // JSON key of the field, e.g. 'workingDirectory'
+    field: string;
+    message: string;
+}
+
+// Rejection from load_config / save_config
+export interface ConfigError {
+    kind: 'invalid' | 'io';
+    message: string;
+    errors: ConfigFieldError[];
+}
+
+// Returned by take_config_recovery after a damaged config.json was replaced
+export interface ConfigRecovery {
+    error: string;
+    // File name of the backup restored; null when none was usable
+    backup: string | null;
+    // Where the damaged file was moved
+    corruptCopy: string | null;
+}
+
+// What an outside edit of config.json changed, by id
+export interface ConfigDiff {
+    addedApps: string[];
+    removedApps: string[];
+    changedApps: string[];
+    addedGroups: string[];
+    removedGroups: string[];
+    changedGroups: string[];
+    // Anything outside apps and groups
+    settingsChanged: boolean;
+}
+
 // Modal state
 export type ModalState =
     | { type: 'closed' }
     | { type: 'add' }
     | { type: 'edit'; appId: string }
+    | { type: 'group'; groupId?: string }
     | { type: 'settings' }
     | { type: 'port-killer' }
     | { type: 'task-killer' }
@@ -50,10 +172,123 @@ export interface PortInfo {
     pid: number;
     name: string;
     protocol: string;
+    localAddress: string;
+    family: 'IPv4' | 'IPv6';
+    // TCP only
+    state: string | null;
+}
+
+export interface ConnectionInfo {
+    protocol: string;
+    family: 'IPv4' | 'IPv6';
+    localAddress: string;
+    localPort: number;
+    remoteAddress: string | null;
+    remotePort: number | null;
+    state: string | null;
+    pid: number;
+    name: string;
+}
+
+// Payload of the app-stopped event; exit details are missing when the
+// process wasn't started by AppCtrl (external stop, cancelled restart)
+export interface AppStoppedEvent {
+    appId: string;
+    exitCode?: number | null;
+    signal?: number | null;
+    signalName?: string | null;
+    runtimeMs?: number;
+}
+
+// Payload of the app-trigger event, sent when a trigger rule matches a line
+export interface AppTriggerEvent {
+    appId: string;
+    // seq of the matching line
+    seq: number;
+    stream: LogStream;
+    line: string;
+    pattern: string;
+    captures: (string | null)[];
+    // 'none' when the rule has no action or it already fired this run
+    action: TriggerAction;
+    errorCount: number;
 }
 
 export interface TaskInfo {
     pid: number;
     name: string;
     memory: string;
+    commandLine: string | null;
+    user: string | null;
+}
+
+// Rejection value of kill_process_by_pid / kill_process_by_name
+export interface ProcessError {
+    kind: 'accessDenied' | 'noSuchProcess' | 'alreadyExited' | 'unsupported' | 'os';
+    pid: number | null;
+    message: string;
+}
+
+// Persistent per-app logs (list_app_log_runs / read_app_log)
+export interface LogRunSummary {
+    runId: string;
+    segments: number;
+    sizeBytes: number;
+    lastWrite: string;
+}
+
+export type LogStream = 'stdout' | 'stderr' | 'system';
+
+/** A run of equally styled text; colors are palette names ('red', 'brightBlue') or '#rrggbb'. */
+export interface LogSpan {
+    text: string;
+    fg?: string;
+    bg?: string;
+    bold?: boolean;
+    dim?: boolean;
+    italic?: boolean;
+    underline?: boolean;
+}
+
+/** One line of app output, as returned by get_app_logs. */
+export interface LogRecord {
+    /** Per app, keeps counting across runs. */
+    seq: number;
+    timestamp: string;
+    stream: LogStream;
+    runId: string | null;
+    /** The line without ANSI escape codes. */
+    text: string;
+    /** Styling from ANSI escape codes; absent for plain lines. */
+    spans?: LogSpan[];
+    /** Unfinished line (prompt, progress bar); replaced by the next record of its stream. */
+    partial: boolean;
+}
+
+export interface AppOutputEvent extends LogRecord {
+    appId: string;
+}
+
+/** One line in the log panel; lines added by the frontend itself have no seq. */
+export interface LogEntry {
+    seq?: number;
+    timestamp: string;
+    stream: LogStream;
+    runId?: string | null;
+    text: string;
+    spans?: LogSpan[];
+    partial?: boolean;
+}
+
+export interface LogFileLine {
+    timestamp: string;
+    stream: LogStream;
+    text: string;
+}
+
+export interface LogPage {
+    runId: string;
+    start: number;
+    total: number;
+    lines: LogFileLine[];
 }